@@ -0,0 +1,45 @@
+use std::hint::black_box;
+use std::path::Path;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use vello::Scene;
+use chess_game::game::{Diagram, SvgOptions, STARTING_POSITION};
+
+// only the scenes are built, nothing is rendered so no GPU is needed
+const POSITIONS: [(&str, &str); 2] = [
+    ("start", STARTING_POSITION),
+    ("kiwipete", "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -"),
+];
+
+fn scene_building(c: &mut Criterion) {
+    let mut diagram = Diagram::load(Path::new("config"), None).unwrap();
+
+    let mut group = c.benchmark_group("full board scene");
+    for (name, fen) in POSITIONS {
+        group.bench_with_input(BenchmarkId::from_parameter(name), fen, |b, fen| b.iter(|| {
+            let mut scene = Scene::new();
+            diagram.draw_fen(black_box(fen), 1.0, &mut scene).unwrap();
+            scene
+        }));
+    }
+    group.finish();
+
+    diagram.set_annotations("[%cal Ge2e4,Rd7d5,Bg1f3][%csl Gd5,Re4]");
+    let mut group = c.benchmark_group("annotated board scene");
+    for (name, fen) in POSITIONS {
+        group.bench_with_input(BenchmarkId::from_parameter(name), fen, |b, fen| b.iter(|| {
+            let mut scene = Scene::new();
+            diagram.draw_fen(black_box(fen), 1.0, &mut scene).unwrap();
+            scene
+        }));
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("svg document");
+    for (name, fen) in POSITIONS {
+        group.bench_with_input(BenchmarkId::from_parameter(name), fen, |b, fen| b.iter(|| diagram.svg_fen(black_box(fen), SvgOptions::default()).unwrap()));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, scene_building);
+criterion_main!(benches);
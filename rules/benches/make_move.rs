@@ -0,0 +1,70 @@
+use std::hint::black_box;
+use criterion::{criterion_group, criterion_main, Criterion};
+use chess_rules::{fen, ChessBoard, PieceRegistry};
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -";
+
+fn perft_clone(board: &ChessBoard, registry: &PieceRegistry, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    board.pseudo_legal_moves(registry)
+        .map(|played| {
+            let mut after = *board;
+            after.move_piece(played);
+            perft_clone(&after, registry, depth - 1)
+        })
+        .sum()
+}
+
+fn perft_unmake(board: &mut ChessBoard, registry: &PieceRegistry, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let moves: Vec<_> = board.pseudo_legal_moves(registry).collect();
+    moves.into_iter()
+        .map(|played| {
+            let undo = board.make_move(played);
+            let nodes = perft_unmake(board, registry, depth - 1);
+            board.unmake_move(undo);
+            nodes
+        })
+        .sum()
+}
+
+fn make_move(c: &mut Criterion) {
+    let registry = PieceRegistry::standard();
+    let board = fen::parse(KIWIPETE, &registry).unwrap();
+    let moves: Vec<_> = board.pseudo_legal_moves(&registry).collect();
+
+    let mut group = c.benchmark_group("every move of kiwipete");
+    group.bench_function("clone", |b| b.iter(|| {
+        for &played in &moves {
+            let mut after = black_box(board);
+            after.move_piece(played);
+            black_box(&after);
+        }
+    }));
+    group.bench_function("make and unmake", |b| {
+        let mut board = board;
+        b.iter(|| {
+            for &played in &moves {
+                let undo = black_box(&mut board).make_move(played);
+                black_box(&board);
+                board.unmake_move(undo);
+            }
+        })
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("pseudo-legal perft 2 of kiwipete");
+    group.bench_function("clone", |b| b.iter(|| perft_clone(black_box(&board), &registry, 2)));
+    group.bench_function("make and unmake", |b| {
+        let mut board = board;
+        b.iter(|| perft_unmake(black_box(&mut board), &registry, 2))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, make_move);
+criterion_main!(benches);
@@ -0,0 +1,81 @@
+use std::hint::black_box;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use chess_rules::{fen, BitBoard, ChessBoard, PieceRegistry, Position};
+
+const POSITIONS: [(&str, &str); 3] = [
+    ("start", fen::STARTING_POSITION),
+    ("kiwipete", "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -"),
+    ("endgame", "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - -"),
+];
+
+fn perft<P: Position>(position: &mut P, registry: &PieceRegistry, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let moves: Vec<_> = position.legal_moves(registry).collect();
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+    moves.into_iter()
+        .map(|played| {
+            let undo = position.make_move(played);
+            let nodes = perft(position, registry, depth - 1);
+            position.unmake_move(undo);
+            nodes
+        })
+        .sum()
+}
+
+fn boards(registry: &PieceRegistry) -> impl Iterator<Item = (&'static str, ChessBoard, BitBoard)> + '_ {
+    POSITIONS.into_iter().map(|(name, position)| {
+        let board = fen::parse(position, registry).unwrap();
+        (name, board, BitBoard::new(&board, registry))
+    })
+}
+
+fn move_generation(c: &mut Criterion) {
+    let registry = PieceRegistry::standard();
+
+    let mut group = c.benchmark_group("pseudo-legal moves");
+    for (name, board, bitboard) in boards(&registry) {
+        group.bench_with_input(BenchmarkId::new("grid", name), &board, |b, board| b.iter(|| black_box(board).pseudo_legal_moves(&registry).count()));
+        group.bench_with_input(BenchmarkId::new("bitboard", name), &bitboard, |b, bitboard| b.iter(|| black_box(bitboard).pseudo_legal_moves().count()));
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("legal moves");
+    for (name, board, bitboard) in boards(&registry) {
+        group.bench_with_input(BenchmarkId::new("grid", name), &board, |b, board| b.iter(|| black_box(board).legal_moves(&registry).count()));
+        group.bench_with_input(BenchmarkId::new("bitboard", name), &bitboard, |b, bitboard| b.iter(|| black_box(bitboard).legal_moves().count()));
+    }
+    group.finish();
+}
+
+fn perft_depths(c: &mut Criterion) {
+    let registry = PieceRegistry::standard();
+    let mut group = c.benchmark_group("perft");
+    group.sample_size(10);
+    for (name, board, bitboard) in boards(&registry) {
+        for depth in [2, 3] {
+            let id = format!("{name} depth {depth}");
+            group.bench_function(BenchmarkId::new("grid", &id), |b| b.iter(|| perft(&mut black_box(board), &registry, depth)));
+            group.bench_function(BenchmarkId::new("bitboard", &id), |b| {
+                let mut bitboard = bitboard.clone();
+                b.iter(|| perft(black_box(&mut bitboard), &registry, depth))
+            });
+        }
+    }
+    group.finish();
+}
+
+fn fen_parsing(c: &mut Criterion) {
+    let registry = PieceRegistry::standard();
+    let mut group = c.benchmark_group("fen");
+    for (name, position) in POSITIONS {
+        group.bench_with_input(BenchmarkId::from_parameter(name), position, |b, position| b.iter(|| fen::parse(black_box(position), &registry)));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, move_generation, perft_depths, fen_parsing);
+criterion_main!(benches);
@@ -0,0 +1,598 @@
+use std::iter;
+use std::sync::Arc;
+use crate::chess_board::{en_passant_key, gcd, piece_key, ChessBoard, Piece, Player, Undo, BLACK_TO_MOVE};
+use crate::grid::{Grid, BOARD_SIZE};
+use crate::piece_registry::{Movement, MovementKind, PieceKind, PieceRegistry};
+use crate::position::{with_promotions, Move, Position, Square};
+use crate::selection::MoveKind;
+
+// cases are numbered row by row from the top left corner, the bit of (x, y) is y * 8 + x
+const CASES: usize = BOARD_SIZE * BOARD_SIZE;
+
+type Masks = Box<[u64; CASES]>;
+
+fn bit((x, y): Square) -> u64 {
+    1 << (y * BOARD_SIZE + x)
+}
+
+fn square(index: usize) -> Square {
+    (index % BOARD_SIZE, index / BOARD_SIZE)
+}
+
+fn on_board(x: i32, y: i32) -> bool {
+    (0..BOARD_SIZE as i32).contains(&x) && (0..BOARD_SIZE as i32).contains(&y)
+}
+
+/// the cases of a mask, from the lowest bit
+fn cases(mut mask: u64) -> impl Iterator<Item = usize> {
+    iter::from_fn(move || {
+        let index = mask.trailing_zeros() as usize;
+        mask &= mask.wrapping_sub(1);
+        (index < CASES).then_some(index)
+    })
+}
+
+fn masks(mut mask: impl FnMut(i32, i32) -> u64) -> Masks {
+    let mut masks = Box::new([0; CASES]);
+    for (index, value) in masks.iter_mut().enumerate() {
+        let (x, y) = square(index);
+        *value = mask(x as i32, y as i32);
+    }
+    masks
+}
+
+/// One movement of a piece, with what it reaches from every case of the board.
+enum Step {
+    Blocking(Masks),
+    Eating(Masks),
+    NotFriend(Masks),
+    // the cases in line up to the edge, `forward` when they have higher bits than the start
+    Trailing { rays: Masks, forward: bool },
+    // the destination, and the cases that must be empty to get there
+    FirstMove(Box<[(u64, u64); CASES]>),
+    EnPassant(Masks),
+    Castling(i32),
+}
+
+impl Step {
+    fn new(Movement(kind, dx, dy): Movement, player: Player) -> Self {
+        let dy = match player {
+            Player::Black => dy,
+            Player::White => -dy,
+        };
+        let offset = move |x: i32, y: i32| if on_board(x + dx, y + dy) { bit(((x + dx) as usize, (y + dy) as usize)) } else { 0 };
+        match kind {
+            MovementKind::Blocking => Step::Blocking(masks(offset)),
+            MovementKind::Eating => Step::Eating(masks(offset)),
+            MovementKind::NotFriend => Step::NotFriend(masks(offset)),
+            MovementKind::EnPassant => Step::EnPassant(masks(offset)),
+            MovementKind::Castling => Step::Castling(dx),
+            MovementKind::Trailing => Step::Trailing {
+                rays: masks(|x, y| {
+                    let mut ray = 0;
+                    let mut i = 1;
+                    while (dx, dy) != (0, 0) && on_board(x + dx * i, y + dy * i) {
+                        ray |= bit(((x + dx * i) as usize, (y + dy * i) as usize));
+                        i += 1;
+                    }
+                    ray
+                }),
+                forward: dy * BOARD_SIZE as i32 + dx > 0,
+            },
+            MovementKind::FirstMove => {
+                let mut paths = Box::new([(0, 0); CASES]);
+                let steps = gcd(dx.abs(), dy.abs()).max(1);
+                for (index, path) in paths.iter_mut().enumerate() {
+                    let (x, y) = square(index);
+                    let (x, y) = (x as i32, y as i32);
+                    if on_board(x + dx, y + dy) {
+                        let way = (1..=steps).fold(0, |way, i| way | bit(((x + dx / steps * i) as usize, (y + dy / steps * i) as usize)));
+                        *path = (bit(((x + dx) as usize, (y + dy) as usize)), way);
+                    }
+                }
+                Step::FirstMove(paths)
+            }
+        }
+    }
+}
+
+/// The movements of the pieces of a registry, worked out once for every case.
+struct Tables {
+    steps: Vec<[Vec<Step>; 2]>, //by kind, then black and white
+    royal: Vec<bool>,
+    promotions: Vec<Vec<PieceKind>>,
+}
+
+impl Tables {
+    fn new(registry: &PieceRegistry) -> Self {
+        let kinds: Vec<_> = (0..=u8::MAX).map(PieceKind)
+            .map_while(|kind| Some((kind, registry.get_movement(kind)?)))
+            .collect();
+        let steps = |movements: &[Movement], player| movements.iter().map(|&movement| Step::new(movement, player)).collect();
+        Self {
+            steps: kinds.iter().map(|(_, movements)| [steps(movements, Player::Black), steps(movements, Player::White)]).collect(),
+            royal: kinds.iter().map(|&(kind, _)| registry.is_royal(kind)).collect(),
+            promotions: kinds.iter().map(|&(kind, _)| registry.get_promotions(kind).to_vec()).collect(),
+        }
+    }
+}
+
+/// The same position as a [`ChessBoard`], stored as one bit per case for each side and kind of piece.
+/// It finds the moves much faster, but only knows the pieces of the registry it was made with:
+/// the registry given to the methods of [`Position`] is ignored.
+#[derive(Clone)]
+pub struct BitBoard {
+    tables: Arc<Tables>,
+    players: [u64; 2], //black then white
+    kinds: Vec<u64>,
+    unmoved: u64,
+    turn: Player,
+    en_passant: Option<(Square, Square)>,
+    last_move: Option<(Square, Square)>,
+    hash: u64,
+}
+
+impl BitBoard {
+    /// the position of `board`, with the movements of `registry`
+    pub fn new(board: &ChessBoard, registry: &PieceRegistry) -> Self {
+        let mut bitboard = Self {
+            tables: Arc::new(Tables::new(registry)),
+            players: [0; 2],
+            kinds: Vec::new(),
+            unmoved: 0,
+            turn: board.turn(),
+            en_passant: board.en_passant(),
+            last_move: board.last_move(),
+            hash: 0,
+        };
+        for (x, y, piece) in board.iter() {
+            bitboard.set((x, y), piece.copied());
+        }
+        bitboard.hash = board.hash();
+        bitboard
+    }
+
+    /// the same position on a grid, without the last move
+    pub fn to_board(&self) -> ChessBoard {
+        let mut grid = Grid::splat(None);
+        for index in cases(self.players[0] | self.players[1]) {
+            let (x, y) = square(index);
+            *grid.get_mut(x, y) = self.get_piece((x, y));
+        }
+        ChessBoard::with_position(grid, self.turn, self.en_passant)
+    }
+
+    /// the side to move
+    pub fn turn(&self) -> Player {
+        self.turn
+    }
+
+    /// the piece on a case, which must be on the board
+    pub fn get_piece(&self, case: Square) -> Option<Piece> {
+        let bit = bit(case);
+        let player = match () {
+            _ if self.players[Player::Black as usize] & bit != 0 => Player::Black,
+            _ if self.players[Player::White as usize] & bit != 0 => Player::White,
+            _ => return None,
+        };
+        let kind = self.kinds.iter().position(|kind| kind & bit != 0)?;
+        Some(Piece { player, piece_kind: PieceKind(kind as u8), not_moved: self.unmoved & bit != 0 })
+    }
+
+    /// the same hash as the [`ChessBoard`] of the position
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// put a piece on a case, or empty it, and give back what was there
+    fn set(&mut self, case: Square, piece: Option<Piece>) -> Option<Piece> {
+        let bit = bit(case);
+        let old = self.get_piece(case);
+        if let Some(old) = old {
+            self.players[old.player as usize] &= !bit;
+            self.kinds[old.piece_kind.index()] &= !bit;
+            self.unmoved &= !bit;
+            self.hash ^= piece_key(case.0, case.1, old);
+        }
+        if let Some(piece) = piece {
+            let kind = piece.piece_kind.index();
+            if kind >= self.kinds.len() {
+                self.kinds.resize(kind + 1, 0);
+            }
+            self.players[piece.player as usize] |= bit;
+            self.kinds[kind] |= bit;
+            if piece.not_moved {
+                self.unmoved |= bit;
+            }
+            self.hash ^= piece_key(case.0, case.1, piece);
+        }
+        old
+    }
+
+    /// hand every group of cases the piece on `from` can go to to `add`, in the order of its movements.
+    /// A case given twice takes the last kind, as on the grid
+    fn targets(&self, from: usize, piece: Piece, mut add: impl FnMut(u64, MoveKind)) {
+        let Some(steps) = self.tables.steps.get(piece.piece_kind.index()) else {
+            return;
+        };
+        let player = piece.player as usize;
+        let friends = self.players[player];
+        let opponents = self.players[1 - player];
+        let occupied = friends | opponents;
+        // the cases a piece can go to without a friend on them, taking the opponents
+        let add_reachable = |add: &mut dyn FnMut(u64, MoveKind), reachable: u64| {
+            add(reachable & opponents, MoveKind::Capture);
+            add(reachable & !opponents, MoveKind::Quiet);
+        };
+
+        for step in &steps[player] {
+            match step {
+                Step::Blocking(masks) => add_reachable(&mut add, masks[from] & !occupied),
+                Step::Eating(masks) => add_reachable(&mut add, masks[from] & opponents),
+                Step::NotFriend(masks) => add_reachable(&mut add, masks[from] & !friends),
+                Step::Trailing { rays, forward } => add_reachable(&mut add, Self::ray_reach(rays, *forward, from, occupied) & !friends),
+                Step::FirstMove(paths) => {
+                    let (destination, path) = paths[from];
+                    if piece.not_moved && destination != 0 && path & occupied == 0 {
+                        add(destination, MoveKind::DoubleStep);
+                    }
+                }
+                Step::EnPassant(masks) => if let Some((passed, victim)) = self.en_passant {
+                    if masks[from] & bit(passed) != 0 && opponents & bit(victim) != 0 {
+                        add(bit(passed), MoveKind::EnPassant);
+                    }
+                }
+                Step::Castling(dx) => if self.can_castle(from, piece, *dx) {
+                    let (x, y) = square(from);
+                    add(bit(((x as i32 + dx) as usize, y)), MoveKind::Castling);
+                }
+            }
+        }
+    }
+
+    // the same rule as `ChessBoard::can_castle`
+    fn can_castle(&self, from: usize, piece: Piece, dx: i32) -> bool {
+        if !piece.not_moved || dx == 0 {
+            return false;
+        }
+        let (x, y) = square(from);
+        let (x, y) = (x as i32, y as i32);
+        let occupied = self.players[0] | self.players[1];
+        let step = dx.signum();
+        let mut partner_x = x + step;
+        while on_board(partner_x, y) && occupied & bit((partner_x as usize, y as usize)) == 0 {
+            partner_x += step;
+        }
+        let has_partner = on_board(partner_x, y)
+            && self.players[piece.player as usize] & self.unmoved & bit((partner_x as usize, y as usize)) != 0;
+        let in_reach = (partner_x - (x + dx)) * step > 0;
+        if !(has_partner && in_reach) {
+            return false;
+        }
+        let attacked = self.attacks(piece.player.opponent());
+        (0..=dx.abs()).all(|i| attacked & bit(((x + step * i) as usize, y as usize)) == 0)
+    }
+
+    /// every case a piece of `by` could take on, whatever stands there, like `ChessBoard::is_attacked`
+    fn attacks(&self, by: Player) -> u64 {
+        let occupied = self.players[0] | self.players[1];
+        let mut attacks = 0;
+        for from in cases(self.players[by as usize]) {
+            let Some(steps) = self.get_piece(square(from)).and_then(|piece| self.tables.steps.get(piece.piece_kind.index())) else {
+                continue;
+            };
+            for step in &steps[by as usize] {
+                attacks |= match step {
+                    Step::Eating(masks) | Step::NotFriend(masks) => masks[from],
+                    Step::Trailing { rays, forward } => Self::ray_reach(rays, *forward, from, occupied),
+                    _ => 0,
+                };
+            }
+        }
+        attacks
+    }
+
+    /// the cases of the ray up to the first occupied one, included
+    fn ray_reach(rays: &Masks, forward: bool, from: usize, occupied: u64) -> u64 {
+        let ray = rays[from];
+        let blockers = ray & occupied;
+        match blockers {
+            0 => ray,
+            _ if forward => ray & !rays[blockers.trailing_zeros() as usize],
+            _ => ray & !rays[63 - blockers.leading_zeros() as usize],
+        }
+    }
+
+    fn royal_attacked(&self, player: Player) -> bool {
+        let royal = self.kinds.iter().zip(&self.tables.royal)
+            .filter(|(_, &royal)| royal)
+            .fold(0, |royal, (kind, _)| royal | kind);
+        // the first royal piece, as `ChessBoard::royal_square` finds it
+        cases(royal & self.players[player as usize]).next()
+            .is_some_and(|index| self.attacks(player.opponent()) & (1 << index) != 0)
+    }
+
+    /// the move of the piece on `from` to `to`, like [`ChessBoard::get_move`]
+    pub fn get_move(&self, from: Square, to: Square, kind: MoveKind) -> Option<Move> {
+        let piece = self.get_piece(from)?;
+        let captured = match kind {
+            MoveKind::EnPassant => self.en_passant.and_then(|(_, victim)| self.get_piece(victim)),
+            MoveKind::Castling => None,
+            _ => self.get_piece(to),
+        };
+        Some(Move { from, to, piece, captured, kind })
+    }
+
+    fn piece_moves(&self, from: usize) -> impl Iterator<Item = Move> + '_ {
+        let piece = self.get_piece(square(from));
+        let mut kinds = [None; CASES];
+        let mut reached = 0;
+        if let Some(piece) = piece {
+            self.targets(from, piece, |mask, kind| {
+                reached |= mask;
+                for to in cases(mask) {
+                    kinds[to] = Some(kind);
+                }
+            });
+        }
+        let promotions = piece.and_then(|piece| self.tables.promotions.get(piece.piece_kind.index())).map_or(&[][..], Vec::as_slice);
+        let last_row = match self.turn {
+            Player::Black => BOARD_SIZE - 1,
+            Player::White => 0,
+        };
+
+        cases(reached).filter_map(move |to| {
+            let to = square(to);
+            let kind = match (promotions.first().copied(), kinds[to.1 * BOARD_SIZE + to.0]?) {
+                (Some(into), kind @ (MoveKind::Quiet | MoveKind::Capture | MoveKind::DoubleStep)) if to.1 == last_row => {
+                    MoveKind::Promotion { into, capture: kind == MoveKind::Capture }
+                }
+                (_, kind) => kind,
+            };
+            self.get_move(square(from), to, kind)
+        })
+        .flat_map(move |played| with_promotions(played, promotions))
+    }
+
+    /// every move of the side to move, in the same order as [`ChessBoard::pseudo_legal_moves`]
+    pub fn pseudo_legal_moves(&self) -> impl Iterator<Item = Move> + '_ {
+        cases(self.players[self.turn as usize]).flat_map(|from| self.piece_moves(from))
+    }
+
+    /// every move of the side to move that doesn't leave its royal piece attacked
+    pub fn legal_moves(&self) -> impl Iterator<Item = Move> + '_ {
+        let player = self.turn;
+        let mut scratch = self.clone();
+        self.pseudo_legal_moves().filter(move |&played| {
+            let undo = scratch.make_move(played);
+            let attacked = scratch.royal_attacked(player);
+            scratch.unmake_move(undo);
+            !attacked
+        })
+    }
+
+    /// play a move of this position in place, like [`ChessBoard::make_move`]
+    pub fn make_move(&mut self, played: Move) -> Undo {
+        let Move { from, to, kind, .. } = played;
+        let (en_passant, last_move, hash) = (self.en_passant, self.last_move, self.hash);
+
+        let moved = self.set(from, None);
+        let landed = moved.map(|mut piece| {
+            piece.not_moved = false;
+            if let MoveKind::Promotion { into, .. } = kind {
+                piece.piece_kind = into;
+            }
+            piece
+        });
+        let taken_on = match (kind, en_passant) {
+            (MoveKind::EnPassant, Some((_, victim))) => victim,
+            _ => to,
+        };
+        let captured = self.set(taken_on, None).map(|piece| (taken_on, piece));
+        self.set(to, landed);
+        let partner = match kind {
+            MoveKind::Castling => self.move_castling_partner(from.0, to),
+            _ => None,
+        };
+
+        self.en_passant = match kind {
+            MoveKind::DoubleStep => Some((((from.0 + to.0) / 2, (from.1 + to.1) / 2), to)),
+            _ => None,
+        };
+        self.hash ^= en_passant.map_or(0, |(passed, _)| en_passant_key(passed))
+            ^ self.en_passant.map_or(0, |(passed, _)| en_passant_key(passed))
+            ^ BLACK_TO_MOVE;
+        self.turn = self.turn.opponent();
+        self.last_move = Some((from, to));
+        Undo { played, moved, captured, partner, en_passant, last_move, hash }
+    }
+
+    /// take back the last move made, with the record [`BitBoard::make_move`] gave for it
+    pub fn unmake_move(&mut self, undo: Undo) {
+        let Move { from, to, .. } = undo.played;
+        if let Some((partner_from, partner_to, partner)) = undo.partner {
+            self.set(partner_to, None);
+            self.set(partner_from, Some(partner));
+        }
+        self.set(to, None);
+        if let Some((case, piece)) = undo.captured {
+            self.set(case, Some(piece));
+        }
+        self.set(from, undo.moved);
+        self.en_passant = undo.en_passant;
+        self.last_move = undo.last_move;
+        self.hash = undo.hash;
+        self.turn = self.turn.opponent();
+    }
+
+    fn move_castling_partner(&mut self, from_x: usize, (x, y): Square) -> Option<(Square, Square, Piece)> {
+        let step: i32 = if x > from_x { 1 } else { -1 };
+        let occupied = self.players[0] | self.players[1];
+        let mut partner_x = x as i32 + step;
+        while on_board(partner_x, y as i32) && occupied & bit((partner_x as usize, y)) == 0 {
+            partner_x += step;
+        }
+        if !on_board(partner_x, y as i32) {
+            return None;
+        }
+        let partner = self.set((partner_x as usize, y), None)?;
+        let landing = ((x as i32 - step) as usize, y);
+        self.set(landing, Some(Piece { not_moved: false, ..partner }));
+        Some(((partner_x as usize, y), landing, partner))
+    }
+}
+
+impl Position for BitBoard {
+    type Undo = Undo;
+
+    fn turn(&self) -> Player {
+        self.turn
+    }
+
+    fn piece_at(&self, (x, y): Square) -> Option<Piece> {
+        if x < BOARD_SIZE && y < BOARD_SIZE { self.get_piece((x, y)) } else { None }
+    }
+
+    fn legal_moves<'a>(&'a self, _registry: &'a PieceRegistry) -> impl Iterator<Item = Move> + 'a {
+        self.legal_moves()
+    }
+
+    fn is_check(&self, _registry: &PieceRegistry) -> bool {
+        self.royal_attacked(self.turn)
+    }
+
+    fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    fn make_move(&mut self, played: Move) -> Undo {
+        self.make_move(played)
+    }
+
+    fn unmake_move(&mut self, undo: Undo) {
+        self.unmake_move(undo)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use super::*;
+    use crate::fen;
+    use crate::piece_registry::PieceConfig;
+
+    const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -";
+    const ENDGAME: &str = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - -";
+    const PROMOTIONS: &str = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+
+    fn perft<P: Position + Clone>(position: &P, registry: &PieceRegistry, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        position.legal_moves(registry)
+            .map(|played| {
+                let mut after = position.clone();
+                after.play(played);
+                perft(&after, registry, depth - 1)
+            })
+            .sum()
+    }
+
+    // both boards play every legal move down to `depth`, and must agree on everything on the way
+    fn cross_check(board: &ChessBoard, bitboard: &mut BitBoard, registry: &PieceRegistry, depth: u32) {
+        let moves: Vec<_> = board.legal_moves(registry).collect();
+        assert_eq!(bitboard.legal_moves().collect::<Vec<_>>(), moves);
+        assert_eq!(bitboard.pseudo_legal_moves().collect::<Vec<_>>(), board.pseudo_legal_moves(registry).collect::<Vec<_>>());
+        assert_eq!(bitboard.hash(), board.hash());
+        assert_eq!(Position::is_check(bitboard, registry), board.is_check(registry));
+        assert_eq!(bitboard.to_board().hash(), board.hash());
+        if depth == 0 {
+            return;
+        }
+        for played in moves {
+            let mut after = *board;
+            after.move_piece(played);
+            let undo = bitboard.make_move(played);
+            cross_check(&after, bitboard, registry, depth - 1);
+            bitboard.unmake_move(undo);
+        }
+    }
+
+    /// pieces that move unlike the orthodox ones: a nightrider, a pawn stepping three cases at first
+    /// and sideways, and a royal piece castling over three cases
+    fn fairy_registry() -> PieceRegistry {
+        let piece = |name: &str, symbol: &str, movements: Vec<Movement>, royal: bool, promotion: Option<&str>| PieceConfig {
+            name: name.to_string(),
+            symbol: symbol.to_string(),
+            movements,
+            value: 1,
+            royal,
+            promotions: promotion.into_iter().map(str::to_string).collect(),
+            black_sprite: PathBuf::new(),
+            white_sprite: PathBuf::new(),
+        };
+        use MovementKind::*;
+        let knight = [(1, 2), (2, 1), (1, -2), (2, -1), (-1, 2), (-2, 1), (-1, -2), (-2, -1)];
+        let around = [(0, 1), (0, -1), (1, 0), (-1, 0), (1, 1), (1, -1), (-1, 1), (-1, -1)];
+        PieceRegistry::from_config(&[
+            piece("Pawn", "", vec![
+                Movement(Blocking, 0, 1), Movement(Blocking, 1, 0), Movement(Eating, 1, 1), Movement(Eating, -1, 1),
+                Movement(FirstMove, 0, 3), Movement(EnPassant, 1, 1), Movement(EnPassant, -1, 1),
+            ], false, Some("Nightrider")),
+            piece("Rook", "R", vec![Movement(Trailing, 0, 1), Movement(Trailing, 0, -1), Movement(Trailing, 1, 0), Movement(Trailing, -1, 0)], false, None),
+            piece("Nightrider", "N", knight.iter().map(|&(dx, dy)| Movement(Trailing, dx, dy)).collect(), false, None),
+            piece("Bishop", "B", vec![Movement(Trailing, 1, 1), Movement(Trailing, 1, -1), Movement(Trailing, -1, 1), Movement(Trailing, -1, -1)], false, None),
+            piece("Queen", "Q", around.iter().map(|&(dx, dy)| Movement(Trailing, dx, dy)).collect(), false, None),
+            piece("King", "K", around.iter().map(|&(dx, dy)| Movement(NotFriend, dx, dy))
+                .chain([Movement(Castling, 3, 0), Movement(Castling, -3, 0)])
+                .collect(), true, None),
+        ])
+    }
+
+    #[test]
+    fn perft_matches_the_known_counts() {
+        let registry = PieceRegistry::standard();
+        for (position, depth, nodes) in [
+            (fen::STARTING_POSITION, 3, 8902),
+            (KIWIPETE, 3, 97862),
+            (ENDGAME, 3, 2812),
+            (PROMOTIONS, 1, 6),
+            (PROMOTIONS, 2, 264),
+            (PROMOTIONS, 3, 9467),
+        ] {
+            let board = fen::parse(position, &registry).unwrap();
+            assert_eq!(perft(&board, &registry, depth), nodes, "{position}");
+            assert_eq!(perft(&BitBoard::new(&board, &registry), &registry, depth), nodes, "{position}");
+        }
+    }
+
+    #[test]
+    fn castling_avoids_the_cases_pawns_take_on() {
+        let registry = PieceRegistry::standard();
+        let castles = |position| BitBoard::new(&fen::parse(position, &registry).unwrap(), &registry)
+            .legal_moves()
+            .any(|played| played.kind == MoveKind::Castling);
+        assert!(castles("4k3/8/8/8/8/8/8/4K2R w K -"));
+        assert!(!castles("4k3/8/8/8/8/8/4p3/4K2R w K -"), "in check");
+        assert!(!castles("4k3/8/8/8/8/8/6p1/4K2R w K -"), "through f1");
+        assert!(castles("4k3/8/8/8/8/6p1/8/4K2R w K -"), "the pawn only steps to g2");
+    }
+
+    #[test]
+    fn both_boards_agree() {
+        let registry = PieceRegistry::standard();
+        for (position, depth) in [(fen::STARTING_POSITION, 2), (KIWIPETE, 2), (ENDGAME, 3), (PROMOTIONS, 2)] {
+            let board = fen::parse(position, &registry).unwrap();
+            cross_check(&board, &mut BitBoard::new(&board, &registry), &registry, depth);
+        }
+    }
+
+    #[test]
+    fn both_boards_agree_on_fairy_pieces() {
+        let registry = fairy_registry();
+        for position in [fen::STARTING_POSITION, "r3k2r/1P4P1/8/2p5/8/8/P5p1/R3K2R w KQkq -"] {
+            let board = fen::parse(position, &registry).unwrap();
+            cross_check(&board, &mut BitBoard::new(&board, &registry), &registry, 2);
+        }
+    }
+}
@@ -0,0 +1,816 @@
+use std::mem::replace;
+use std::ops::Range;
+use serde::{Deserialize, Serialize};
+use crate::piece_registry::MovementKind::*;
+use crate::chess_board::Player::{Black, White};
+use crate::grid::{Grid, BOARD_SIZE};
+use crate::piece_registry::{Movement, PieceKind, PieceRegistry};
+use crate::position::{with_promotions, Move, Square};
+use crate::selection::{MoveKind, Selection};
+
+/// One of the two sides.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Player {
+    /// starts on the top rows of the grid
+    Black,
+    /// starts on the bottom rows of the grid, and plays first
+    White
+}
+
+impl Player {
+    fn flip(&mut self) {
+        *self = self.opponent()
+    }
+
+    /// the other side
+    pub fn opponent(self) -> Self {
+        match self {
+            Black => White,
+            White => Black
+        }
+    }
+}
+/// A piece standing on the board.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct Piece {
+    /// the side it belongs to
+    pub player:  Player,
+    /// how it moves, as described by the registry
+    pub piece_kind: PieceKind,
+    /// whether it is still on its starting case, for double steps and castling
+    pub not_moved: bool,
+}
+
+impl Piece {
+    fn new(player:  Player, piece_kind: PieceKind) -> Option<Self> {
+        Some(Self {
+            player,
+            piece_kind,
+            not_moved: true,
+        })
+    }
+
+    fn pawn_row(player: Player) -> [Option<Self>; 8] {
+        [Self::new(player, PieceKind(0)); 8]
+    }
+
+    //todo: add Serde support to put this in a json file
+    fn heavy_row(player: Player) -> [Option<Self>; 8] {
+        [
+            Self::new(player, PieceKind(1)),
+            Self::new(player, PieceKind(2)),
+            Self::new(player, PieceKind(3)),
+            Self::new(player, PieceKind(4)),
+            Self::new(player, PieceKind(5)),
+            Self::new(player, PieceKind(3)),
+            Self::new(player, PieceKind(2)),
+            Self::new(player, PieceKind(1)),
+        ]
+    }
+}
+
+/// A position: the pieces on the grid, the side to move and what the last move allows.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ChessBoard {
+    grid: Grid<Option<Piece>>,
+    turn: Player,
+    last_move: Option<((usize, usize), (usize, usize))>,
+    en_passant: Option<((usize, usize), (usize, usize))>, //the case skipped by the last double step, and the piece that did it
+    hash: u64, //kept up to date by every change, see `compute_hash`
+}
+
+/// What [`ChessBoard::make_move`] changed, to take the move back with [`ChessBoard::unmake_move`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Undo {
+    pub(crate) played: Move,
+    pub(crate) moved: Option<Piece>, //as it was before the move, with its `not_moved` flag
+    pub(crate) captured: Option<(Square, Piece)>,
+    pub(crate) partner: Option<(Square, Square, Piece)>, //where the castling partner came from and went
+    pub(crate) en_passant: Option<(Square, Square)>,
+    pub(crate) last_move: Option<(Square, Square)>,
+    pub(crate) hash: u64,
+}
+
+impl Undo {
+    /// the move that was made
+    pub fn played(&self) -> Move {
+        self.played
+    }
+}
+
+// zobrist keys are mixed from what they stand for rather than read from a table, so any number of kinds works
+fn key(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+pub(crate) fn piece_key(x: usize, y: usize, piece: Piece) -> u64 {
+    let case = (y * BOARD_SIZE + x) as u64;
+    key(case | (piece.piece_kind.index() as u64) << 8 | (piece.player as u64) << 16 | (piece.not_moved as u64) << 17)
+}
+
+pub(crate) fn en_passant_key((x, y): Square) -> u64 {
+    key((y * BOARD_SIZE + x) as u64 | 1 << 20)
+}
+
+pub(crate) const BLACK_TO_MOVE: u64 = 0x2545_f491_4f6c_dd1d;
+
+pub(crate) fn gcd(a: i32, b: i32) -> i32 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+impl Default for ChessBoard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChessBoard {
+    fn new_grid() -> Grid<Option<Piece>> {
+        use Player::*;
+        Grid::from([
+            Piece::heavy_row(Black),
+            Piece::pawn_row(Black),
+            [None; 8],
+            [None; 8],
+            [None; 8],
+            [None; 8],
+            Piece::pawn_row(White),
+            Piece::heavy_row(White),
+        ])
+    }
+
+    /// the starting position of the standard pieces
+    pub fn new() -> Self {
+        Self::with_position(Self::new_grid(), White, None)
+    }
+
+    /// a position set up from its parts, usually read from a FEN record
+    pub fn with_position(grid: Grid<Option<Piece>>, turn: Player, en_passant: Option<((usize, usize), (usize, usize))>) -> Self {
+        let mut board = Self {
+            grid,
+            turn,
+            last_move: None,
+            en_passant,
+            hash: 0,
+        };
+        board.hash = board.compute_hash();
+        board
+    }
+
+    /// a hash of the pieces with their `not_moved` flags, the side to move and the en passant case,
+    /// equal positions have equal hashes whatever the moves that led to them
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    fn compute_hash(&self) -> u64 {
+        let pieces = self.iter()
+            .filter_map(|(x, y, piece)| piece.map(|&piece| piece_key(x, y, piece)))
+            .fold(0, |hash, key| hash ^ key);
+        let turn = if self.turn == Black { BLACK_TO_MOVE } else { 0 };
+        pieces ^ turn ^ self.en_passant.map_or(0, |(passed, _)| en_passant_key(passed))
+    }
+
+    /// put a piece on a case, or empty it, and give back what was there
+    fn set(&mut self, x: usize, y: usize, piece: Option<Piece>) -> Option<Piece> {
+        let old = replace(self.grid.get_mut(x, y), piece);
+        self.hash ^= old.map_or(0, |old| piece_key(x, y, old)) ^ piece.map_or(0, |piece| piece_key(x, y, piece));
+        old
+    }
+
+    /// the side to move
+    pub fn turn(&self) -> Player {
+        self.turn
+    }
+
+    /// the case skipped by the last double step, and the piece that did it
+    pub fn en_passant(&self) -> Option<(Square, Square)> {
+        self.en_passant
+    }
+
+    /// source and destination of the move that led to this position
+    pub fn last_move(&self) -> Option<((usize, usize), (usize, usize))> {
+        self.last_move
+    }
+
+    /// the piece on (x, y), which must be on the board
+    pub fn get_piece(&self, x: usize, y: usize) -> Option<Piece> {
+        *self.grid.get(x, y)
+    }
+
+    fn is_in_grid(x: i32, y: i32) -> bool {
+        const RANGE: Range<i32> = 0..(BOARD_SIZE as i32);
+        RANGE.contains(&x) && RANGE.contains(&y)
+    }
+
+    fn is_empty(&self, x: i32, y: i32) -> bool {
+        Self::is_in_grid(x, y) && self.get_piece(x as usize, y as usize).is_none()
+    }
+
+    /// whether the cases from (x, y) to (x + dx, y + dy) are empty, along the line when there is one
+    fn is_path_empty(&self, x: i32, y: i32, dx: i32, dy: i32) -> bool {
+        let steps = gcd(dx.abs(), dy.abs()).max(1);
+        (1..=steps).all(|i| self.is_empty(x + dx / steps * i, y + dy / steps * i))
+    }
+
+    fn is_opponent(&self, player: Player,  x: i32, y: i32) -> bool {
+        Self::is_in_grid(x, y) && self.get_piece(x as usize, y as usize).is_some_and(|piece| piece.player != player)
+    }
+
+    fn is_not_friend(&self, player: Player,  x: i32, y: i32) -> bool {
+        Self::is_in_grid(x, y) && self.get_piece(x as usize, y as usize).is_none_or(|piece| piece.player != player)
+    }
+
+    /// the moves of the piece on (x, y), if it belongs to the side to move and (x, y) is on the board.
+    /// They may leave its royal piece attacked, see [`ChessBoard::leaves_royal_attacked`]
+    pub fn possible_choice(&self, registry: &PieceRegistry, x: usize, y: usize) -> Option<Selection> {
+        if x >= BOARD_SIZE || y >= BOARD_SIZE {
+            return None
+        }
+        let piece = self.get_piece(x, y)?;
+        if piece.player != self.turn {
+            return None
+        }
+        self.reachable(registry, x, y)
+    }
+
+    /// the moves of the piece on (x, y) that don't leave its royal piece attacked, to show when it is picked up
+    pub fn legal_choice(&self, registry: &PieceRegistry, x: usize, y: usize) -> Option<Selection> {
+        let choice = self.possible_choice(registry, x, y)?.choice;
+        let mut selection = Selection::new(x, y);
+        for (to_x, to_y, kind) in choice.into_destinations() {
+            let legal = self.get_move((x, y), (to_x, to_y), kind)
+                .is_some_and(|played| !self.leaves_royal_attacked(registry, played));
+            if legal {
+                selection.choice.add(to_x as i32, to_y as i32, kind);
+            }
+        }
+        Some(selection)
+    }
+
+    /// the move of the piece on `from` to `to`, `kind` comes from its selection
+    pub fn get_move(&self, from: Square, to: Square, kind: MoveKind) -> Option<Move> {
+        let piece = self.get_piece(from.0, from.1)?;
+        let captured = match kind {
+            MoveKind::EnPassant => self.en_passant.and_then(|(_, (x, y))| self.get_piece(x, y)),
+            MoveKind::Castling => None,
+            _ => self.get_piece(to.0, to.1),
+        };
+        Some(Move { from, to, piece, captured, kind })
+    }
+
+    /// every move of the side to move, including those that leave its royal piece attacked.
+    /// A promotion comes once for each piece it can turn into
+    pub fn pseudo_legal_moves<'a>(&'a self, registry: &'a PieceRegistry) -> impl Iterator<Item = Move> + 'a {
+        self.iter()
+            .filter_map(move |(x, y, _)| self.possible_choice(registry, x, y))
+            .flat_map(move |selection| {
+                let from = (selection.x, selection.y);
+                selection.choice.into_destinations()
+                    .filter_map(move |(x, y, kind)| self.get_move(from, (x, y), kind))
+                    .flat_map(move |played| with_promotions(played, registry.get_promotions(played.piece.piece_kind)))
+            })
+    }
+
+    /// every move of the side to move that doesn't leave its royal piece attacked
+    pub fn legal_moves<'a>(&'a self, registry: &'a PieceRegistry) -> impl Iterator<Item = Move> + 'a {
+        self.pseudo_legal_moves(registry).filter(move |&played| !self.leaves_royal_attacked(registry, played))
+    }
+
+    /// every case the piece at (x, y) can go to, whoever's turn it is
+    fn reachable(&self, registry: &PieceRegistry, x: usize, y: usize) -> Option<Selection> {
+        let piece = self.get_piece(x, y)?;
+        let mut selection = Selection::new(x, y);
+        let flip = match piece.player {
+            Black => 1,
+            White => -1,
+        };
+        let last_row = match piece.player {
+            Black => BOARD_SIZE as i32 - 1,
+            White => 0,
+        };
+        // the selection only shows the default promotion, the moves give every choice
+        let promotion = registry.get_promotions(piece.piece_kind).first().copied();
+
+        let x = x as i32;
+        let y = y as i32;
+
+        let capture_or_quiet = |x: i32, y: i32| if self.is_opponent(piece.player, x, y) {
+            MoveKind::Capture
+        } else {
+            MoveKind::Quiet
+        };
+        // reaching the last row promotes the piece, whatever the movement was
+        let mut add = |x: i32, y: i32, kind: MoveKind| {
+            let kind = match (promotion, kind) {
+                (Some(into), MoveKind::Quiet | MoveKind::Capture | MoveKind::DoubleStep) if y == last_row => {
+                    MoveKind::Promotion { into, capture: kind == MoveKind::Capture }
+                }
+                _ => kind,
+            };
+            selection.choice.add(x, y, kind)
+        };
+
+        for Movement(kind, dx, dy) in registry.get_movement(piece.piece_kind)? {
+            let dy = dy * flip;
+            match kind {
+                Blocking => if self.is_empty(x + dx, y + dy) {
+                    add(x + dx, y + dy, MoveKind::Quiet)
+                }
+                FirstMove => if piece.not_moved && self.is_path_empty(x, y, *dx, dy) {
+                    add(x + dx, y + dy, MoveKind::DoubleStep)
+                }
+                Eating => if self.is_opponent(piece.player, x + dx,y + dy) {
+                    add(x + dx, y + dy, MoveKind::Capture)
+                }
+                NotFriend => if self.is_not_friend(piece.player, x + dx, y + dy) {
+                    add(x + dx, y + dy, capture_or_quiet(x + dx, y + dy))
+                }
+                Trailing => {
+                    let mut i = 1;
+                    while self.is_not_friend(piece.player, x + dx * i, y + dy * i) {
+                        add(x + dx * i, y + dy * i, capture_or_quiet(x + dx * i, y + dy * i));
+                        if self.is_opponent(piece.player,x + dx * i, y + dy * i) { break; }
+                        i += 1;
+                    }
+                }
+                EnPassant => if self.can_take_en_passant(piece.player, x + dx, y + dy) {
+                    add(x + dx, y + dy, MoveKind::EnPassant)
+                }
+                Castling => if self.can_castle(registry, piece, x, y, *dx) {
+                    add(x + dx, y, MoveKind::Castling)
+                }
+            }
+        }
+        Some(selection)
+    }
+
+    fn can_take_en_passant(&self, player: Player, x: i32, y: i32) -> bool {
+        self.en_passant.is_some_and(|((passed_x, passed_y), (victim_x, victim_y))| {
+            (passed_x as i32, passed_y as i32) == (x, y) && self.is_opponent(player, victim_x as i32, victim_y as i32)
+        })
+    }
+
+    /// the piece jumps `dx` cases toward an unmoved friend, which then lands on the case it crossed.
+    /// Neither may have moved, the cases between them must be empty and the jumping piece can't pass through an attack.
+    fn can_castle(&self, registry: &PieceRegistry, piece: Piece, x: i32, y: i32, dx: i32) -> bool {
+        if !piece.not_moved || dx == 0 {
+            return false;
+        }
+        let step = dx.signum();
+        let mut partner_x = x + step;
+        while self.is_empty(partner_x, y) {
+            partner_x += step;
+        }
+        let has_partner = Self::is_in_grid(partner_x, y) && self.get_piece(partner_x as usize, y as usize)
+            .is_some_and(|partner| partner.player == piece.player && partner.not_moved);
+        // the destination has to be strictly before the partner
+        let in_reach = (partner_x - (x + dx)) * step > 0;
+
+        has_partner && in_reach && (0..=dx.abs())
+            .all(|i| !self.is_attacked(registry, piece.player.opponent(), (x + step * i) as usize, y as usize))
+    }
+
+    /// total value of the pieces the player still has on the board
+    pub fn material(&self, registry: &PieceRegistry, player: Player) -> u32 {
+        self.iter()
+            .filter_map(|(_, _, piece)| piece.filter(|piece| piece.player == player))
+            .map(|piece| registry.get_value(piece.piece_kind))
+            .sum()
+    }
+
+    /// the case of the royal piece of `player`
+    pub fn royal_square(&self, registry: &PieceRegistry, player: Player) -> Option<(usize, usize)> {
+        self.iter()
+            .find(|(_, _, piece)| piece.is_some_and(|piece| piece.player == player && registry.is_royal(piece.piece_kind)))
+            .map(|(x, y, _)| (x, y))
+    }
+
+    /// whether a piece of `by` could take on (x, y)
+    pub fn is_attacked(&self, registry: &PieceRegistry, by: Player, x: usize, y: usize) -> bool {
+        self.iter()
+            .filter(|(_, _, piece)| piece.is_some_and(|piece| piece.player == by))
+            .any(|(from_x, from_y, _)| self.attacks(registry, from_x, from_y, x as i32, y as i32))
+    }
+
+    /// whether the piece on (from_x, from_y) could take on (x, y), whatever stands there.
+    /// Only the movements that capture count: a pawn attacks the cases it takes on even when they
+    /// are empty, but not the one it steps to
+    fn attacks(&self, registry: &PieceRegistry, from_x: usize, from_y: usize, x: i32, y: i32) -> bool {
+        let Some(piece) = self.get_piece(from_x, from_y) else {
+            return false;
+        };
+        let flip = match piece.player {
+            Black => 1,
+            White => -1,
+        };
+        let (from_x, from_y) = (from_x as i32, from_y as i32);
+        registry.get_movement(piece.piece_kind).unwrap_or_default().iter().any(|&Movement(kind, dx, dy)| {
+            let dy = dy * flip;
+            match kind {
+                Eating | NotFriend => (from_x + dx, from_y + dy) == (x, y),
+                Trailing => {
+                    let mut i = 1;
+                    while (dx, dy) != (0, 0) && Self::is_in_grid(from_x + dx * i, from_y + dy * i) {
+                        if (from_x + dx * i, from_y + dy * i) == (x, y) {
+                            return true;
+                        }
+                        if !self.is_empty(from_x + dx * i, from_y + dy * i) {
+                            break;
+                        }
+                        i += 1;
+                    }
+                    false
+                }
+                _ => false,
+            }
+        })
+    }
+
+    /// the royal piece of the side to move, if it is in check
+    pub fn checked_square(&self, registry: &PieceRegistry) -> Option<(usize, usize)> {
+        self.royal_square(registry, self.turn)
+            .filter(|&(x, y)| self.is_attacked(registry, self.turn.opponent(), x, y))
+    }
+
+    /// whether the move would leave the royal piece of the side to move attacked, which the rules forbid
+    pub fn leaves_royal_attacked(&self, registry: &PieceRegistry, played: Move) -> bool {
+        let player = self.turn;
+        let mut after = *self;
+        after.move_piece(played);
+        after.royal_square(registry, player)
+            .is_some_and(|(x, y)| after.is_attacked(registry, player.opponent(), x, y))
+    }
+
+    /// play a move of this position, its kind tells which side effects it has
+    pub fn move_piece(&mut self, played: Move) {
+        self.make_move(played);
+    }
+
+    /// play a move of this position in place, the returned record takes it back with [`ChessBoard::unmake_move`]
+    pub fn make_move(&mut self, played: Move) -> Undo {
+        let Move { from: (from_x, from_y), to: (x, y), kind, .. } = played;
+        let (en_passant, last_move, hash) = (self.en_passant, self.last_move, self.hash);
+
+        let moved = self.set(from_x, from_y, None);
+        let landed = moved.map(|mut piece| {
+            piece.not_moved = false;
+            if let MoveKind::Promotion { into, .. } = kind {
+                piece.piece_kind = into;
+            }
+            piece
+        });
+        // the pawn taken en passant isn't on the destination
+        let taken_on = match (kind, en_passant) {
+            (MoveKind::EnPassant, Some((_, victim))) => victim,
+            _ => (x, y),
+        };
+        let captured = self.set(taken_on.0, taken_on.1, None).map(|piece| (taken_on, piece));
+        self.set(x, y, landed);
+        let partner = match kind {
+            MoveKind::Castling => self.move_castling_partner(from_x, x, y),
+            _ => None,
+        };
+
+        self.en_passant = match kind {
+            MoveKind::DoubleStep => Some((((from_x + x) / 2, (from_y + y) / 2), (x, y))),
+            _ => None,
+        };
+        self.hash ^= en_passant.map_or(0, |(passed, _)| en_passant_key(passed))
+            ^ self.en_passant.map_or(0, |(passed, _)| en_passant_key(passed))
+            ^ BLACK_TO_MOVE;
+        self.turn.flip();
+        self.last_move = Some(((from_x, from_y), (x, y)));
+        Undo { played, moved, captured, partner, en_passant, last_move, hash }
+    }
+
+    /// take back the last move made, with the record [`ChessBoard::make_move`] gave for it
+    pub fn unmake_move(&mut self, undo: Undo) {
+        let Move { from, to, .. } = undo.played;
+        if let Some((partner_from, partner_to, partner)) = undo.partner {
+            *self.grid.get_mut(partner_to.0, partner_to.1) = None;
+            *self.grid.get_mut(partner_from.0, partner_from.1) = Some(partner);
+        }
+        *self.grid.get_mut(to.0, to.1) = None;
+        if let Some(((x, y), piece)) = undo.captured {
+            *self.grid.get_mut(x, y) = Some(piece);
+        }
+        *self.grid.get_mut(from.0, from.1) = undo.moved;
+        self.en_passant = undo.en_passant;
+        self.last_move = undo.last_move;
+        self.hash = undo.hash;
+        self.turn.flip();
+    }
+
+    /// the partner lands on the case the piece crossed, gives back where it came from and went as it was before
+    fn move_castling_partner(&mut self, from_x: usize, x: usize, y: usize) -> Option<(Square, Square, Piece)> {
+        let step: i32 = if x > from_x { 1 } else { -1 };
+        let mut partner_x = x as i32 + step;
+        while self.is_empty(partner_x, y as i32) {
+            partner_x += step;
+        }
+        if !Self::is_in_grid(partner_x, y as i32) {
+            return None;
+        }
+        let partner = self.set(partner_x as usize, y, None)?;
+        let landing_x = (x as i32 - step) as usize;
+        self.set(landing_x, y, Some(Piece { not_moved: false, ..partner }));
+        Some(((partner_x as usize, y), (landing_x, y), partner))
+    }
+
+    /// every case with the piece on it, in the order of [`Grid::iter`]
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, Option<&Piece>)> {
+        self.grid.iter().map(|(x, y, piece)| (x, y, piece.as_ref()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use crate::fen;
+    use crate::notation::{parse_move, parse_square, square_name};
+    use crate::position::Position;
+
+    const POSITIONS: [&str; 4] = [
+        fen::STARTING_POSITION,
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - -",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    ];
+
+    // every move down to `depth` is made and taken back, with the hash kept up to date on the way
+    fn walk(board: &mut ChessBoard, registry: &PieceRegistry, depth: u32) {
+        if depth == 0 {
+            return;
+        }
+        let moves: Vec<_> = board.pseudo_legal_moves(registry).collect();
+        for played in moves {
+            let before = *board;
+            let undo = board.make_move(played);
+            assert_eq!(board.hash(), board.compute_hash(), "hash after {played:?}");
+            walk(board, registry, depth - 1);
+            board.unmake_move(undo);
+            assert_eq!(*board, before, "taking back {played:?}");
+        }
+    }
+
+    #[test]
+    fn unmaking_restores_the_position() {
+        let registry = PieceRegistry::standard();
+        for position in POSITIONS {
+            walk(&mut fen::parse(position, &registry).unwrap(), &registry, 3);
+        }
+    }
+
+    #[test]
+    fn transpositions_share_a_hash() {
+        let registry = PieceRegistry::standard();
+        let play = |moves: &[&str]| moves.iter().fold(ChessBoard::new(), |mut board, text| {
+            board.move_piece(parse_move(&board, &registry, text).unwrap());
+            board
+        });
+        assert_eq!(play(&["Nf3", "Nc6", "Nc3"]).hash(), play(&["Nc3", "Nc6", "Nf3"]).hash());
+        assert_ne!(play(&["Nf3", "Nc6", "Nc3"]).hash(), play(&["Nf3", "Nc6", "Nc3", "Nb8"]).hash());
+        // the same pieces, without the en passant capture the double step allows
+        let double_step = play(&["e4", "Nf6", "e5", "d5"]);
+        let mut without_en_passant = ChessBoard { en_passant: None, ..double_step };
+        without_en_passant.hash = without_en_passant.compute_hash();
+        assert_ne!(double_step.hash(), without_en_passant.hash());
+    }
+
+    fn square(name: &str) -> Square {
+        parse_square(name).unwrap()
+    }
+
+    fn piece(board: &ChessBoard, name: &str) -> Piece {
+        let (x, y) = square(name);
+        board.get_piece(x, y).unwrap()
+    }
+
+    // the destinations of the piece on `from` by name, sorted
+    fn choices(position: &str, from: &str) -> Vec<(String, MoveKind)> {
+        let registry = PieceRegistry::standard();
+        let board = fen::parse(position, &registry).unwrap();
+        let (x, y) = square(from);
+        let mut choices: Vec<_> = board.possible_choice(&registry, x, y).unwrap().choice.into_destinations()
+            .map(|(x, y, kind)| (square_name(x, y), kind))
+            .collect();
+        choices.sort_by(|a, b| a.0.cmp(&b.0));
+        choices
+    }
+
+    fn expected(choices: &[(&str, MoveKind)]) -> Vec<(String, MoveKind)> {
+        choices.iter().map(|&(name, kind)| (name.to_string(), kind)).collect()
+    }
+
+    #[test]
+    fn moving_flips_the_turn_and_marks_the_piece_as_moved() {
+        let registry = PieceRegistry::standard();
+        let mut board = ChessBoard::new();
+        assert_eq!(board.turn(), White);
+        assert!(board.iter().filter_map(|(_, _, piece)| piece).all(|piece| piece.not_moved));
+
+        board.move_piece(board.get_move(square("e2"), square("e4"), MoveKind::DoubleStep).unwrap());
+        assert_eq!(board.turn(), Black);
+        assert_eq!(board.get_piece(4, 6), None);
+        assert!(!piece(&board, "e4").not_moved);
+        assert!(piece(&board, "d2").not_moved);
+
+        board.move_piece(parse_move(&board, &registry, "Nf6").unwrap());
+        assert_eq!(board.turn(), White);
+        assert!(!piece(&board, "f6").not_moved);
+        assert!(piece(&board, "e8").not_moved);
+        assert_eq!(board.iter().filter_map(|(_, _, piece)| piece).filter(|piece| !piece.not_moved).count(), 2);
+    }
+
+    #[test]
+    fn castling_moves_both_pieces() {
+        let registry = PieceRegistry::standard();
+        let mut board = fen::parse("r3k2r/8/8/8/8/8/8/R3K2R w KQkq -", &registry).unwrap();
+        board.move_piece(board.get_move(square("e1"), square("g1"), MoveKind::Castling).unwrap());
+        assert_eq!(board.turn(), Black);
+        assert!(!piece(&board, "g1").not_moved);
+        assert!(!piece(&board, "f1").not_moved);
+        assert!(piece(&board, "a1").not_moved);
+        assert_eq!(board.get_piece(7, 7), None);
+    }
+
+    #[test]
+    fn blocking_and_first_move_need_empty_cases() {
+        use MoveKind::*;
+        assert_eq!(choices(fen::STARTING_POSITION, "e2"), expected(&[("e3", Quiet), ("e4", DoubleStep)]));
+        assert_eq!(choices("4k3/8/8/8/4n3/8/4P3/4K3 w - -", "e2"), expected(&[("e3", Quiet)]));
+        // the first move doesn't jump over a piece
+        assert_eq!(choices("4k3/8/8/8/8/4n3/4P3/4K3 w - -", "e2"), Vec::new());
+        assert_eq!(choices("4k3/8/8/8/8/4P3/8/4K3 w - -", "e3"), expected(&[("e4", Quiet)]));
+        let queen = PieceRegistry::standard().get_promotions(PieceKind(0))[0];
+        assert_eq!(choices("4k3/P7/8/8/8/8/8/4K3 w - -", "a7"), expected(&[("a8", Promotion { into: queen, capture: false })]));
+        assert_eq!(choices("4k3/8/8/8/8/8/p7/4K3 b - -", "a2"), expected(&[("a1", Promotion { into: queen, capture: false })]));
+    }
+
+    #[test]
+    fn promotions_give_a_move_for_each_piece() {
+        let registry = PieceRegistry::standard();
+        let board = fen::parse("1n2k3/P7/8/8/8/8/8/4K3 w - -", &registry).unwrap();
+        let promotions: Vec<_> = board.legal_moves(&registry)
+            .filter(|played| played.from == square("a7"))
+            .map(|played| (square_name(played.to.0, played.to.1), played.promotion().map(|into| registry.get_symbol(into)), played.is_capture()))
+            .collect();
+        assert_eq!(promotions, [
+            ("a8".to_string(), Some("Q"), false), ("a8".to_string(), Some("R"), false),
+            ("a8".to_string(), Some("B"), false), ("a8".to_string(), Some("N"), false),
+            ("b8".to_string(), Some("Q"), true), ("b8".to_string(), Some("R"), true),
+            ("b8".to_string(), Some("B"), true), ("b8".to_string(), Some("N"), true),
+        ]);
+    }
+
+    #[test]
+    fn eating_only_takes_opponents() {
+        use MoveKind::*;
+        assert_eq!(choices("4k3/8/8/3p1N2/4P3/8/8/4K3 w - -", "e4"), expected(&[("d5", Capture), ("e5", Quiet)]));
+        // on the edge there is only one side to take on, the other doesn't wrap around the board
+        assert_eq!(choices("4k3/8/8/1p5p/P7/8/8/4K3 w - -", "a4"), expected(&[("a5", Quiet), ("b5", Capture)]));
+        assert_eq!(choices("4k3/8/8/8/8/8/6P1/4K3 b - -", "e8"), expected(&[("d7", Quiet), ("d8", Quiet), ("e7", Quiet), ("f7", Quiet), ("f8", Quiet)]));
+    }
+
+    #[test]
+    fn not_friend_stays_on_the_board() {
+        use MoveKind::*;
+        assert_eq!(choices("4k3/8/8/8/8/8/3pP3/4K3 w - -", "e1"), expected(&[("d1", Quiet), ("d2", Capture), ("f1", Quiet), ("f2", Quiet)]));
+        assert_eq!(choices("4k3/8/8/8/8/8/8/N3K3 w - -", "a1"), expected(&[("b3", Quiet), ("c2", Quiet)]));
+        assert_eq!(choices("4k2n/8/6P1/8/8/8/8/4K3 b - -", "h8"), expected(&[("f7", Quiet), ("g6", Capture)]));
+    }
+
+    #[test]
+    fn trailing_stops_at_the_first_piece_and_the_edge() {
+        use MoveKind::*;
+        assert_eq!(choices("4k3/8/8/8/8/8/8/Rn2K3 w - -", "a1"), expected(&[
+            ("a2", Quiet), ("a3", Quiet), ("a4", Quiet), ("a5", Quiet), ("a6", Quiet), ("a7", Quiet), ("a8", Quiet), ("b1", Capture),
+        ]));
+        assert_eq!(choices("4k3/8/8/8/8/2P5/1B6/4K3 w - -", "b2"), expected(&[("a1", Quiet), ("a3", Quiet), ("c1", Quiet)]));
+    }
+
+    #[test]
+    fn en_passant_takes_the_pawn_that_skipped_the_case() {
+        use MoveKind::*;
+        assert_eq!(choices("4k3/8/8/3pP3/8/8/8/4K3 w - d6", "e5"), expected(&[("d6", EnPassant), ("e6", Quiet)]));
+        assert_eq!(choices("4k3/8/8/Pp6/8/8/8/4K3 w - b6", "a5"), expected(&[("a6", Quiet), ("b6", EnPassant)]));
+        assert_eq!(choices("4k3/8/8/Pp6/8/8/8/4K3 w - -", "a5"), expected(&[("a6", Quiet)]));
+        assert_eq!(choices("4k3/8/8/8/6pP/8/8/4K3 b - h3", "g4"), expected(&[("g3", Quiet), ("h3", EnPassant)]));
+    }
+
+    #[test]
+    fn castling_needs_unmoved_partners_and_safe_cases() {
+        use MoveKind::*;
+        let king_steps = [("d1", Quiet), ("d2", Quiet), ("e2", Quiet), ("f1", Quiet), ("f2", Quiet)];
+        let castled = |castling: &[(&'static str, MoveKind)]| {
+            let mut all = king_steps.to_vec();
+            all.extend_from_slice(castling);
+            all.sort_by(|a, b| a.0.cmp(b.0));
+            expected(&all)
+        };
+        assert_eq!(choices("r3k2r/8/8/8/8/8/8/R3K2R w KQkq -", "e1"), castled(&[("c1", Castling), ("g1", Castling)]));
+        assert_eq!(choices("r3k2r/8/8/8/8/8/8/R3K2R w K -", "e1"), castled(&[("g1", Castling)]));
+        assert_eq!(choices("r3k2r/8/8/8/8/8/8/R3K2R w - -", "e1"), castled(&[]));
+        // a piece in between, or a case the king crosses under attack
+        assert_eq!(choices("r3k2r/8/8/8/8/8/8/RN2K2R w KQkq -", "e1"), castled(&[("g1", Castling)]));
+        assert_eq!(choices("3rk2r/8/8/8/8/8/8/R3K2R w KQk -", "e1"), castled(&[("g1", Castling)]));
+    }
+
+    #[test]
+    fn pawns_attack_the_cases_they_take_on() {
+        let registry = PieceRegistry::standard();
+        let board = fen::parse("4k3/8/8/8/8/4p3/8/4K3 w - -", &registry).unwrap();
+        let attacked = |name| {
+            let (x, y) = square(name);
+            board.is_attacked(&registry, Black, x, y)
+        };
+        assert!(attacked("d2") && attacked("f2"), "empty cases on the diagonals");
+        assert!(!attacked("e2"), "the case it steps to");
+    }
+
+    #[test]
+    fn no_castling_out_of_or_through_a_pawn_attack() {
+        let registry = PieceRegistry::standard();
+        let castles = |position| fen::parse(position, &registry).unwrap()
+            .legal_moves(&registry)
+            .any(|played| played.kind == MoveKind::Castling);
+        assert!(castles("4k3/8/8/8/8/8/8/4K2R w K -"));
+        assert!(!castles("4k3/8/8/8/8/8/4p3/4K2R w K -"), "in check");
+        assert!(!castles("4k3/8/8/8/8/8/6p1/4K2R w K -"), "through f1");
+        assert!(!castles("4k3/8/8/8/8/8/7p/4K2R w K -"), "onto g1");
+        assert!(castles("4k3/8/8/8/8/6p1/8/4K2R w K -"), "the pawn only steps to g2");
+    }
+
+    #[test]
+    fn legal_choice_leaves_out_moves_into_check() {
+        use MoveKind::*;
+        let registry = PieceRegistry::standard();
+        // the knight is pinned by the rook, and the king can't step next to it
+        let board = fen::parse("4k3/8/8/8/4r3/8/4N3/4K3 w - -", &registry).unwrap();
+        let destinations = |from| {
+            let (x, y) = square(from);
+            board.legal_choice(&registry, x, y).unwrap().choice.into_destinations()
+                .map(|(x, y, kind)| (square_name(x, y), kind))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(destinations("e2"), Vec::new());
+        assert_eq!(destinations("e1"), expected(&[("d2", Quiet), ("f2", Quiet), ("d1", Quiet), ("f1", Quiet)]));
+    }
+
+    #[test]
+    fn no_choice_outside_the_board_or_for_the_waiting_side() {
+        let registry = PieceRegistry::standard();
+        let board = ChessBoard::new();
+        assert!(board.possible_choice(&registry, 4, 4).is_none());
+        assert!(board.possible_choice(&registry, 1, 1).is_none(), "not the turn of black");
+        assert!(board.possible_choice(&registry, BOARD_SIZE, 0).is_none());
+        assert!(board.possible_choice(&registry, 0, usize::MAX).is_none());
+    }
+
+    fn royal_count(board: &ChessBoard, registry: &PieceRegistry, player: Player) -> usize {
+        board.iter()
+            .filter_map(|(_, _, piece)| piece)
+            .filter(|piece| piece.player == player && registry.is_royal(piece.piece_kind))
+            .count()
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        // each choice picks one of the legal moves, the game stops early when there is none
+        #[test]
+        fn legal_moves_keep_one_king_per_side(choices in prop::collection::vec(any::<prop::sample::Index>(), 0..80)) {
+            let registry = PieceRegistry::standard();
+            let mut board = ChessBoard::new();
+            for choice in choices {
+                let moves: Vec<_> = board.legal_moves(&registry).collect();
+                if moves.is_empty() {
+                    break;
+                }
+                let played = *choice.get(&moves);
+                for (x, y) in [played.from, played.to] {
+                    prop_assert!(x < BOARD_SIZE && y < BOARD_SIZE, "{:?} leaves the board", played);
+                }
+                board.move_piece(played);
+                prop_assert_eq!(royal_count(&board, &registry, White), 1);
+                prop_assert_eq!(royal_count(&board, &registry, Black), 1);
+            }
+        }
+
+        #[test]
+        fn out_of_range_cases_are_empty(x in 0..usize::MAX, y in 0..usize::MAX) {
+            let registry = PieceRegistry::standard();
+            let board = ChessBoard::new();
+            let on_board = x < BOARD_SIZE && y < BOARD_SIZE;
+            prop_assert_eq!(board.piece_at((x, y)).is_some(), on_board && !(2..6).contains(&y));
+            if !on_board {
+                prop_assert!(board.possible_choice(&registry, x, y).is_none());
+            }
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use std::fmt::{Display, Formatter};
+use crate::chess_board::{ChessBoard, Piece, Player};
+use crate::grid::{Grid, BOARD_SIZE};
+use crate::notation::{parse_square, square_name};
+use crate::piece_registry::{MovementKind, PieceKind, PieceRegistry};
+
+/// The starting position of orthodox chess.
+pub const STARTING_POSITION: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// The field of a FEN record that couldn't be read.
+#[non_exhaustive]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum FenError {
+    /// the rows of pieces
+    Placement(String),
+    /// a letter no piece of the registry uses
+    UnknownPiece(char),
+    /// the side to move
+    Turn(String),
+    /// the castling rights
+    Castling(String),
+    /// the case skipped by the last double step
+    EnPassant(String),
+}
+
+impl Display for FenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FenError::Placement(field) => write!(f, "invalid piece placement `{field}`"),
+            FenError::UnknownPiece(letter) => write!(f, "no piece uses the letter `{letter}`"),
+            FenError::Turn(field) => write!(f, "invalid side to move `{field}`"),
+            FenError::Castling(field) => write!(f, "invalid castling rights `{field}`"),
+            FenError::EnPassant(field) => write!(f, "invalid en passant square `{field}`"),
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
+/// the letter of a piece in FEN records: its notation symbol, or `P` for the pawns that don't have one
+fn letter(registry: &PieceRegistry, kind: PieceKind) -> Option<char> {
+    let symbol = registry.get_symbol(kind);
+    if symbol.is_empty() { Some('P') } else { symbol.chars().next() }
+}
+
+fn home_row(player: Player) -> usize {
+    match player {
+        Player::White => BOARD_SIZE - 1,
+        Player::Black => 0,
+    }
+}
+
+/// Read a position written in Forsyth-Edwards Notation, with the pieces of the registry.
+/// The move counters are optional and ignored. Castling rights are given either as `KQkq`
+/// or as the files of the castling partners (`HAha`), and become the `not_moved` flags of the pieces.
+pub fn parse(fen: &str, registry: &PieceRegistry) -> Result<ChessBoard, FenError> {
+    let mut fields = fen.split_whitespace();
+    let placement = fields.next().unwrap_or_default();
+    let turn = fields.next().unwrap_or("w");
+    let castling = fields.next().unwrap_or("-");
+    let en_passant = fields.next().unwrap_or("-");
+
+    let kinds: Vec<_> = (0..=u8::MAX).map(PieceKind)
+        .take_while(|&kind| registry.get_movement(kind).is_some())
+        .collect();
+    let mut grid = Grid::splat(None);
+    let rows: Vec<_> = placement.split('/').collect();
+    if rows.len() != BOARD_SIZE {
+        return Err(FenError::Placement(placement.to_string()));
+    }
+    for (y, row) in rows.into_iter().enumerate() {
+        let mut x = 0;
+        for character in row.chars() {
+            if let Some(empty) = character.to_digit(10) {
+                x += empty as usize;
+                continue;
+            }
+            if x >= BOARD_SIZE {
+                return Err(FenError::Placement(placement.to_string()));
+            }
+            let player = if character.is_ascii_uppercase() { Player::White } else { Player::Black };
+            let piece_kind = kinds.iter().copied()
+                .find(|&kind| letter(registry, kind) == Some(character.to_ascii_uppercase()))
+                .ok_or(FenError::UnknownPiece(character))?;
+            // pieces with a first move haven't moved as long as they stand on the row they start from
+            let start_row = match player {
+                Player::White => BOARD_SIZE - 2,
+                Player::Black => 1,
+            };
+            let has_first_move = registry.get_movement(piece_kind)
+                .is_some_and(|movements| movements.iter().any(|movement| movement.0 == MovementKind::FirstMove));
+            let not_moved = has_first_move && y == start_row;
+            *grid.get_mut(x, y) = Some(Piece { player, piece_kind, not_moved });
+            x += 1;
+        }
+        if x != BOARD_SIZE {
+            return Err(FenError::Placement(placement.to_string()));
+        }
+    }
+
+    let turn = match turn {
+        "w" => Player::White,
+        "b" => Player::Black,
+        _ => return Err(FenError::Turn(turn.to_string())),
+    };
+
+    if castling != "-" {
+        for right in castling.chars() {
+            let player = if right.is_ascii_uppercase() { Player::White } else { Player::Black };
+            let y = home_row(player);
+            let royal_x = (0..BOARD_SIZE)
+                .find(|&x| grid.get(x, y).is_some_and(|piece: Piece| piece.player == player && registry.is_royal(piece.piece_kind)))
+                .ok_or_else(|| FenError::Castling(castling.to_string()))?;
+            let friend = |x: &usize| grid.get(*x, y).is_some_and(|piece: Piece| piece.player == player);
+            // the partner of a side is the outermost friend, unless its file is given
+            let partner_x = match right.to_ascii_lowercase() {
+                'k' => (royal_x + 1..BOARD_SIZE).rev().find(friend),
+                'q' => (0..royal_x).find(friend),
+                file @ 'a'..='z' => Some(file as usize - 'a' as usize).filter(|x| *x < BOARD_SIZE && friend(x)),
+                _ => None,
+            }.ok_or_else(|| FenError::Castling(castling.to_string()))?;
+            for x in [royal_x, partner_x] {
+                if let Some(piece) = grid.get_mut(x, y) {
+                    piece.not_moved = true;
+                }
+            }
+        }
+    }
+
+    let en_passant = match en_passant {
+        "-" => None,
+        square => {
+            let (x, y) = parse_square(square).ok_or_else(|| FenError::EnPassant(square.to_string()))?;
+            // the pawn that skipped the square stands one step further, in the direction of the player who moved it
+            let pawn_y = match turn {
+                Player::Black => y.checked_sub(1),
+                Player::White => Some(y + 1).filter(|&y| y < BOARD_SIZE),
+            }.ok_or_else(|| FenError::EnPassant(square.to_string()))?;
+            Some(((x, y), (x, pawn_y)))
+        }
+    };
+
+    Ok(ChessBoard::with_position(grid, turn, en_passant))
+}
+
+/// Write a position in Forsyth-Edwards Notation, as [`parse`] reads it back. `full_move` is the number of the move
+/// being played, the board doesn't count the moves since the last capture so that counter is always 0.
+/// A side can castle when its royal piece and the outermost friend on that side of it haven't moved.
+pub fn write(board: &ChessBoard, registry: &PieceRegistry, full_move: usize) -> String {
+    let rows: Vec<String> = (0..BOARD_SIZE).map(|y| {
+        let mut row = String::new();
+        let mut empty = 0;
+        for x in 0..BOARD_SIZE {
+            let Some(piece) = board.get_piece(x, y) else {
+                empty += 1;
+                continue;
+            };
+            if empty > 0 {
+                row += &empty.to_string();
+                empty = 0;
+            }
+            let letter = letter(registry, piece.piece_kind).unwrap_or('?');
+            row.push(if piece.player == Player::White { letter } else { letter.to_ascii_lowercase() });
+        }
+        if empty > 0 {
+            row += &empty.to_string();
+        }
+        row
+    }).collect();
+
+    let mut castling = String::new();
+    for player in [Player::White, Player::Black] {
+        let y = home_row(player);
+        let friend = |x: &usize| board.get_piece(*x, y).is_some_and(|piece| piece.player == player);
+        let unmoved = |x: usize| board.get_piece(x, y).is_some_and(|piece| piece.not_moved);
+        let royal = |x: &usize| board.get_piece(*x, y).is_some_and(|piece| piece.player == player && registry.is_royal(piece.piece_kind));
+        let Some(royal_x) = (0..BOARD_SIZE).find(royal).filter(|&x| unmoved(x)) else {
+            continue;
+        };
+        for (right, partner_x) in [('K', (royal_x + 1..BOARD_SIZE).rev().find(friend)), ('Q', (0..royal_x).find(friend))] {
+            if partner_x.is_some_and(unmoved) {
+                castling.push(if player == Player::White { right } else { right.to_ascii_lowercase() });
+            }
+        }
+    }
+    if castling.is_empty() {
+        castling.push('-');
+    }
+
+    let turn = if board.turn() == Player::White { "w" } else { "b" };
+    let en_passant = board.en_passant().map_or("-".to_string(), |((x, y), _)| square_name(x, y));
+    format!("{} {turn} {castling} {en_passant} 0 {full_move}", rows.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn written_positions_read_back() {
+        let registry = PieceRegistry::standard();
+        for fen in [
+            STARTING_POSITION,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k3/8/8/8/3pP3/8/8/4K2R b Kq e3 0 23",
+            "8/8/8/8/8/8/8/4K2k w - - 0 60",
+        ] {
+            let board = parse(fen, &registry).unwrap();
+            assert_eq!(write(&board, &registry, fen.rsplit(' ').next().unwrap().parse().unwrap()), fen);
+        }
+    }
+}
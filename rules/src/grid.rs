@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+
+/// The number of rows and columns of the board.
+pub const BOARD_SIZE: usize = 8;
+
+/// A value for every case of the board, indexed by `(x, y)` from the top left corner.
+#[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Grid<T> {
+    grid: [[T; BOARD_SIZE]; BOARD_SIZE]
+}
+
+impl<T: Copy> Grid<T> {
+    /// the same value on every case
+    pub fn splat(value: T) -> Self {
+        Self {
+            grid: [[value; BOARD_SIZE]; BOARD_SIZE]
+        }
+    }
+}
+
+impl<T: Clone> Clone for Grid<T> {
+    fn clone(&self) -> Self {
+        Self{
+            grid: self.grid.clone(),
+        }
+    }
+}
+
+impl<T: Copy> Copy for Grid<T> {}
+
+impl<T> Grid<T> {
+    /// a grid from its rows, the top one first
+    pub fn from(grid: [[T; BOARD_SIZE]; BOARD_SIZE]) -> Self {
+        Self {
+            grid
+        }
+    }
+
+    /// every case with its coordinates, row by row from the top and left to right in a row
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, &T)> {
+        let ys = self.grid.iter().enumerate();
+        let xs = ys.map(|(y, xs)| xs.iter().enumerate().map(move |(x, piece)| (x, y, piece) ));
+        xs.flatten()
+    }
+
+    /// the value on (x, y), which must be on the board
+    pub fn get(&self, x: usize, y: usize) -> &T {
+        &self.grid[y][x]
+    }
+
+    /// the value on (x, y), which must be on the board
+    pub fn get_mut(&mut self, x: usize, y: usize) -> &mut T {
+        &mut self.grid[y][x]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iter_goes_row_by_row_from_the_top() {
+        let mut grid = Grid::splat(0);
+        for y in 0..BOARD_SIZE {
+            for x in 0..BOARD_SIZE {
+                *grid.get_mut(x, y) = y * BOARD_SIZE + x;
+            }
+        }
+        let cases: Vec<_> = grid.iter().map(|(x, y, &value)| (x, y, value)).collect();
+        assert_eq!(cases.len(), BOARD_SIZE * BOARD_SIZE);
+        assert_eq!(cases[..3], [(0, 0, 0), (1, 0, 1), (2, 0, 2)]);
+        assert_eq!(cases[BOARD_SIZE], (0, 1, BOARD_SIZE));
+        for (index, (x, y, value)) in cases.into_iter().enumerate() {
+            assert_eq!(value, index);
+            assert_eq!(*grid.get(x, y), value);
+        }
+    }
+
+    #[test]
+    fn rows_are_given_from_the_top() {
+        let mut rows = [['.'; BOARD_SIZE]; BOARD_SIZE];
+        rows[0][BOARD_SIZE - 1] = 'h';
+        rows[BOARD_SIZE - 1][0] = 'a';
+        let grid = Grid::from(rows);
+        assert_eq!(*grid.get(BOARD_SIZE - 1, 0), 'h');
+        assert_eq!(*grid.get(0, BOARD_SIZE - 1), 'a');
+        assert_eq!(grid.iter().find(|(_, _, &value)| value == 'h'), Some((BOARD_SIZE - 1, 0, &'h')));
+        assert_eq!(grid.iter().last(), Some((BOARD_SIZE - 1, BOARD_SIZE - 1, &'.')));
+    }
+}
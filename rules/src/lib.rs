@@ -0,0 +1,48 @@
+//! The rules of the game, without anything to draw them: the board, the pieces and how they move,
+//! the notation and FEN. Servers, engines and tests can depend on this crate alone.
+//!
+//! The pieces aren't hardcoded, a [`PieceRegistry`] describes how each of them moves, and every
+//! position is read through it. Tools should go through the [`Position`] trait, which only speaks
+//! of [`Move`]s and [`GameResult`]s, rather than the grid of the board.
+//!
+//! ```
+//! use chess_rules::{notation, ChessBoard, PieceRegistry, Position};
+//!
+//! let registry = PieceRegistry::standard();
+//! let mut board = ChessBoard::new();
+//! assert_eq!(board.legal_moves(&registry).count(), 20);
+//!
+//! for san in ["f3", "e5", "g4", "Qh4"] {
+//!     let played = board.legal_moves(&registry)
+//!         .find(|&played| notation::san(&board, &registry, played).trim_end_matches(['+', '#']) == san)
+//!         .unwrap();
+//!     board.play(played);
+//! }
+//! assert_eq!(board.result(&registry).map(|result| result.score()), Some("0-1"));
+//! ```
+
+#![warn(missing_docs)]
+
+/// a value for every case of the board
+pub mod grid;
+/// the destinations of a selected piece
+pub mod selection;
+/// the board, its pieces and how they move on it
+pub mod chess_board;
+/// the kinds of pieces and their movements
+pub mod piece_registry;
+/// algebraic notation, to write and read moves
+pub mod notation;
+/// Forsyth-Edwards Notation, to read and write positions
+pub mod fen;
+/// the interface of a position, independent of how the board is stored
+pub mod position;
+/// a faster board for engines, one bit per case
+pub mod bitboard;
+
+pub use bitboard::BitBoard;
+pub use chess_board::{ChessBoard, Piece, Player, Undo};
+pub use fen::FenError;
+pub use piece_registry::{PieceKind, PieceRegistry};
+pub use position::{GameResult, Move, Position, Square};
+pub use selection::MoveKind;
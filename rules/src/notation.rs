@@ -0,0 +1,217 @@
+use std::fmt::{Display, Formatter};
+use crate::chess_board::ChessBoard;
+use crate::grid::BOARD_SIZE;
+use crate::piece_registry::PieceRegistry;
+use crate::position::{Move, Square};
+use crate::selection::MoveKind;
+
+// grid coordinates start from the top left corner, which is a8 on a standard board
+
+/// the letter of a column
+pub fn file_name(x: usize) -> char {
+    (b'a' + x as u8) as char
+}
+
+/// the number of a row
+pub fn rank_name(y: usize) -> String {
+    (BOARD_SIZE - y).to_string()
+}
+
+/// the name of a case, like `e4`
+pub fn square_name(x: usize, y: usize) -> String {
+    format!("{}{}", file_name(x), rank_name(y))
+}
+
+/// standard algebraic notation of a move, `board` is the position before the move is played
+pub fn san(board: &ChessBoard, registry: &PieceRegistry, played: Move) -> String {
+    let (from_x, _) = played.from;
+    let (x, y) = played.to;
+
+    let mut san = if played.kind == MoveKind::Castling {
+        let side = if x > from_x { "O-O" } else { "O-O-O" };
+        side.to_string()
+    } else {
+        let symbol = registry.get_symbol(played.piece.piece_kind);
+        let mut san = symbol.to_string();
+        if symbol.is_empty() {
+            // pieces without a symbol are pawn-like, their file is enough to tell them apart
+            if played.is_capture() {
+                san.push(file_name(from_x));
+            }
+        } else {
+            san += &disambiguation(board, registry, played);
+        }
+        if played.is_capture() {
+            san.push('x');
+        }
+        san += &square_name(x, y);
+        if let Some(into) = played.promotion() {
+            san.push('=');
+            san += registry.get_symbol(into);
+        }
+        san
+    };
+
+    let mut after = *board;
+    after.move_piece(played);
+    if after.checked_square(registry).is_some() {
+        san.push(if after.legal_moves(registry).next().is_none() { '#' } else { '+' });
+    }
+    san
+}
+
+/// the origin file, rank or both, when another identical piece could legally go to the same case
+fn disambiguation(board: &ChessBoard, registry: &PieceRegistry, played: Move) -> String {
+    let from = played.from;
+    let rivals: Vec<_> = board.legal_moves(registry)
+        .filter(|rival| rival.to == played.to && rival.from != from && rival.piece.piece_kind == played.piece.piece_kind)
+        .map(|rival| rival.from)
+        .collect();
+
+    if rivals.is_empty() {
+        String::new()
+    } else if rivals.iter().all(|&(x, _)| x != from.0) {
+        file_name(from.0).to_string()
+    } else if rivals.iter().all(|&(_, y)| y != from.1) {
+        rank_name(from.1)
+    } else {
+        square_name(from.0, from.1)
+    }
+}
+
+/// the case named by `text`, like `e4`
+pub fn parse_square(text: &str) -> Option<(usize, usize)> {
+    let mut chars = text.chars();
+    let file = chars.next()?;
+    let x = (file as usize).checked_sub('a' as usize).filter(|&x| x < BOARD_SIZE)?;
+    let rank: usize = chars.as_str().parse().ok()?;
+    let y = BOARD_SIZE.checked_sub(rank).filter(|_| rank > 0)?;
+    Some((x, y))
+}
+
+/// Why a typed move wasn't found.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum MoveEntryError {
+    /// the text isn't written like a move
+    Unreadable,
+    /// no piece can play that move
+    Illegal,
+    /// several moves are written that way
+    Ambiguous,
+}
+
+impl Display for MoveEntryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MoveEntryError::Unreadable => write!(f, "not a move"),
+            MoveEntryError::Illegal => write!(f, "illegal move"),
+            MoveEntryError::Ambiguous => write!(f, "ambiguous move"),
+        }
+    }
+}
+
+/// check marks, capture signs and annotation symbols are optional when typing a move
+fn simplify(san: &str) -> String {
+    san.replace('0', "O")
+        .chars()
+        .filter(|c| !matches!(c, '+' | '#' | 'x' | ':' | '=' | '!' | '?'))
+        .collect()
+}
+
+/// read a typed move, either in standard algebraic notation (`Nf3`, `exd5`, `O-O`)
+/// or with its coordinates (`g1f3`, `e7e8q`), and find it among the legal moves of the side to move
+pub fn parse_move(board: &ChessBoard, registry: &PieceRegistry, text: &str) -> Result<Move, MoveEntryError> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(MoveEntryError::Unreadable);
+    }
+    let moves = board.legal_moves(registry);
+
+    // a letter after the squares picks the piece to promote into, the default one is taken without it
+    let (squares, letter) = match text.strip_suffix(|c: char| c.is_ascii_alphabetic()) {
+        Some(rest) if rest.ends_with(|c: char| c.is_ascii_digit()) => (rest, text.get(rest.len()..)),
+        _ => (text, None),
+    };
+    if let Some((from, to)) = parse_coordinates(squares) {
+        let mut candidates = moves.filter(|played| (played.from, played.to) == (from, to));
+        let found = match letter {
+            None => candidates.next(),
+            Some(letter) => candidates.find(|played| {
+                played.promotion().is_some_and(|into| registry.get_symbol(into).eq_ignore_ascii_case(letter))
+            }),
+        };
+        return found.ok_or(MoveEntryError::Illegal);
+    }
+
+    let wanted = simplify(text);
+    let mut matching = moves
+        .filter(|&played| simplify(&san(board, registry, played)) == wanted);
+    match (matching.next(), matching.next()) {
+        (Some(found), None) => Ok(found),
+        (Some(_), Some(_)) => Err(MoveEntryError::Ambiguous),
+        (None, _) if wanted.chars().any(|c| c.is_ascii_digit()) || wanted.starts_with('O') => Err(MoveEntryError::Illegal),
+        (None, _) => Err(MoveEntryError::Unreadable),
+    }
+}
+
+/// two square names one after the other, optionally separated by a dash
+pub fn parse_coordinates(text: &str) -> Option<(Square, Square)> {
+    let split = text.char_indices().skip(1).find(|&(_, c)| c.is_ascii_alphabetic())?.0;
+    let (from, to) = text.split_at(split);
+    Some((parse_square(from.trim_end_matches('-'))?, parse_square(to)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fen;
+
+    // the knight on f3 is pinned to the king by the bishop, only the one on b3 can go to d2
+    const PINNED_TWIN: &str = "4k3/8/8/7b/8/1N3N2/8/3K4 w - -";
+
+    #[test]
+    fn a_pinned_rival_needs_no_disambiguation() {
+        let registry = PieceRegistry::standard();
+        let board = fen::parse(PINNED_TWIN, &registry).unwrap();
+        let played = parse_move(&board, &registry, "Nd2").unwrap();
+        assert_eq!(played.from, parse_square("b3").unwrap());
+        assert_eq!(san(&board, &registry, played), "Nd2");
+    }
+
+    #[test]
+    fn moves_leaving_the_king_attacked_are_illegal() {
+        let registry = PieceRegistry::standard();
+        let board = fen::parse(PINNED_TWIN, &registry).unwrap();
+        assert_eq!(parse_move(&board, &registry, "f3d2"), Err(MoveEntryError::Illegal));
+        assert_eq!(parse_move(&board, &registry, "Nfd2"), Err(MoveEntryError::Illegal));
+        assert_eq!(parse_move(&board, &registry, "Ne5"), Err(MoveEntryError::Illegal));
+        assert_eq!(parse_move(&board, &registry, "Kc2"), Ok(board.get_move((3, 7), (2, 6), MoveKind::Quiet).unwrap()));
+    }
+
+    #[test]
+    fn the_promotion_letter_picks_the_piece() {
+        let registry = PieceRegistry::standard();
+        let board = fen::parse("4k3/1P6/8/8/8/8/8/4K3 w - -", &registry).unwrap();
+        let promoted = |text| parse_move(&board, &registry, text).map(|played| played.promotion().map(|into| registry.get_symbol(into)));
+        assert_eq!(promoted("b7b8"), Ok(Some("Q")));
+        assert_eq!(promoted("b7b8n"), Ok(Some("N")));
+        assert_eq!(promoted("b7-b8R"), Ok(Some("R")));
+        assert_eq!(promoted("b8=B"), Ok(Some("B")));
+        assert_eq!(promoted("b7b8k"), Err(MoveEntryError::Illegal));
+        assert_eq!(promoted("e1e2q"), Err(MoveEntryError::Illegal));
+    }
+
+    #[test]
+    fn mate_is_marked() {
+        let registry = PieceRegistry::standard();
+        let mut board = ChessBoard::new();
+        for text in ["f3", "e5", "g4"] {
+            board.move_piece(parse_move(&board, &registry, text).unwrap());
+        }
+        let played = parse_move(&board, &registry, "Qh4#").unwrap();
+        assert_eq!(san(&board, &registry, played), "Qh4#");
+        let checking = fen::parse("4k3/8/8/8/8/8/8/R3K3 w - -", &registry).unwrap();
+        assert_eq!(san(&checking, &registry, parse_move(&checking, &registry, "Ra8").unwrap()), "Ra8+");
+    }
+}
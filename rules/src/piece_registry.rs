@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+
+/// How a piece may use one of its offsets.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum MovementKind {
+    /// only available if the case is empty
+    Blocking,
+    /// only available if the case contains an opponent
+    Eating,
+    /// available if the case is empty or contains an opponent
+    NotFriend,
+    /// all multiples available, but stop after the first occupied case
+    Trailing,
+    /// like blocking only on the first move, the cases on the way must be empty too
+    FirstMove,
+    /// only available on the case an opponent skipped with its first move, taking that opponent
+    EnPassant,
+    /// horizontal jump toward an unmoved friend, which lands on the crossed case. Both must not have moved
+    Castling,
+}
+
+/// One way a piece moves: the kind of movement and its offset `(dx, dy)`, with `dy` toward the opponent.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Movement(pub MovementKind, pub i32, pub i32);
+
+/// A piece as described in the config file.
+#[derive(Serialize, Deserialize)]
+pub struct PieceConfig {
+    /// unique name of the piece
+    pub name: String,
+    /// letter used in the notation, pawns don't have one
+    #[serde(default)]
+    pub symbol: String,
+    /// every way the piece moves
+    pub movements: Vec<Movement>,
+    /// material worth, used to weigh the captured pieces
+    #[serde(default)]
+    pub value: u32,
+    /// losing this piece loses the game, it is the one that can be in check
+    #[serde(default)]
+    pub royal: bool,
+    /// names of the pieces this one can turn into on the last row, the first is the one picked by default
+    #[serde(default)]
+    pub promotions: Vec<String>,
+    /// image of the black piece, only read by the interface
+    pub black_sprite: PathBuf,
+    /// image of the white piece
+    pub white_sprite: PathBuf,
+}
+
+struct PieceData {
+    name: String,
+    symbol: String,
+    movements: Vec<Movement>,
+    value: u32,
+    royal: bool,
+    promotions: Vec<PieceKind>,
+}
+
+/// A kind of piece of a registry, kinds are numbered in the order the pieces were registered.
+#[derive(Hash, Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PieceKind(pub(crate) u8);
+
+impl PieceKind {
+    /// the position of the piece in its registry, starting from 0
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Every kind of piece of a game and how it moves.
+pub struct PieceRegistry {
+    id_allocator: u8,
+    pieces: HashMap<PieceKind, PieceData>, //could be a little more optimized with a Vec, but it's not a big deal
+}
+
+impl PieceRegistry {
+    fn new() -> Self {
+        Self {
+            id_allocator: 0,
+            pieces: HashMap::new(),
+        }
+    }
+
+    /// the pieces of orthodox chess, for when there is no config to read them from
+    pub fn standard() -> Self {
+        let mut registry = Self::new();
+        let pawn = registry.register_piece(PieceData {
+            name: "Pawn".to_string(),
+            symbol: "".to_string(),
+            movements: vec![
+                Movement(MovementKind::Blocking, 0, 1),
+                Movement(MovementKind::Eating, 1, 1),
+                Movement(MovementKind::Eating, -1, 1),
+                Movement(MovementKind::FirstMove, 0, 2),
+                Movement(MovementKind::EnPassant, 1, 1),
+                Movement(MovementKind::EnPassant, -1, 1),
+            ],
+            value: 1,
+            royal: false,
+            promotions: Vec::new(),
+        });
+        let rook = registry.register_piece(PieceData {
+            name: "Rook".to_string(),
+            symbol: "R".to_string(),
+            movements: vec![
+                Movement(MovementKind::Trailing, 0, 1),
+                Movement(MovementKind::Trailing, 0, -1),
+                Movement(MovementKind::Trailing, 1, 0),
+                Movement(MovementKind::Trailing, -1, 0),
+            ],
+            value: 5,
+            royal: false,
+            promotions: Vec::new(),
+        });
+        let knight = registry.register_piece(PieceData {
+            name: "Knight".to_string(),
+            symbol: "N".to_string(),
+            movements: vec![
+                Movement(MovementKind::NotFriend, 1, 2),
+                Movement(MovementKind::NotFriend, 2, 1),
+                Movement(MovementKind::NotFriend, 1, -2),
+                Movement(MovementKind::NotFriend, 2, -1),
+                Movement(MovementKind::NotFriend, -1, 2),
+                Movement(MovementKind::NotFriend, -2, 1),
+                Movement(MovementKind::NotFriend, -1, -2),
+                Movement(MovementKind::NotFriend, -2, -1),
+            ],
+            value: 3,
+            royal: false,
+            promotions: Vec::new(),
+        });
+        let bishop = registry.register_piece(PieceData {
+            name: "Bishop".to_string(),
+            symbol: "B".to_string(),
+            movements: vec![
+                Movement(MovementKind::Trailing, 1, 1),
+                Movement(MovementKind::Trailing, 1, -1),
+                Movement(MovementKind::Trailing, -1, 1),
+                Movement(MovementKind::Trailing, -1, -1),
+            ],
+            value: 3,
+            royal: false,
+            promotions: Vec::new(),
+        });
+        let queen = registry.register_piece(PieceData {
+            name: "Queen".to_string(),
+            symbol: "Q".to_string(),
+            movements: vec![
+                Movement(MovementKind::Trailing, 0, 1),
+                Movement(MovementKind::Trailing, 0, -1),
+                Movement(MovementKind::Trailing, 1, 0),
+                Movement(MovementKind::Trailing, -1, 0),
+                Movement(MovementKind::Trailing, 1, 1),
+                Movement(MovementKind::Trailing, 1, -1),
+                Movement(MovementKind::Trailing, -1, 1),
+                Movement(MovementKind::Trailing, -1, -1),
+            ],
+            value: 9,
+            royal: false,
+            promotions: Vec::new(),
+        });
+        let _king = registry.register_piece(PieceData {
+            name: "King".to_string(),
+            symbol: "K".to_string(),
+            movements: vec![
+                Movement(MovementKind::NotFriend, 0, 1),
+                Movement(MovementKind::NotFriend, 0, -1),
+                Movement(MovementKind::NotFriend, 1, 0),
+                Movement(MovementKind::NotFriend, -1, 0),
+                Movement(MovementKind::NotFriend, 1, 1),
+                Movement(MovementKind::NotFriend, 1, -1),
+                Movement(MovementKind::NotFriend, -1, 1),
+                Movement(MovementKind::NotFriend, -1, -1),
+                Movement(MovementKind::Castling, 2, 0),
+                Movement(MovementKind::Castling, -2, 0),
+            ],
+            value: 0,
+            royal: true,
+            promotions: Vec::new(),
+        });
+        registry.pieces.get_mut(&pawn).unwrap().promotions = vec![queen, rook, bishop, knight];
+        registry
+    }
+
+
+    /// the pieces described by a config, their kinds follow the order of the list
+    pub fn from_config(pieces: &[PieceConfig]) -> Self {
+        let mut registry = Self::new();
+
+        for piece in pieces {
+            registry.register_piece(PieceData {
+                name: piece.name.clone(),
+                symbol: piece.symbol.clone(),
+                movements: piece.movements.clone(),
+                value: piece.value,
+                royal: piece.royal,
+                promotions: Vec::new(),
+            });
+        }
+
+        // promotions are resolved once every piece is known, so they can refer to a piece declared later
+        for (kind, piece) in pieces.iter().enumerate() {
+            let promotions = piece.promotions.iter().filter_map(|name| registry.find_by_name(name)).collect();
+            registry.pieces.get_mut(&PieceKind(kind as u8)).unwrap().promotions = promotions;
+        }
+        registry
+    }
+
+    /// the piece names in the order of their kinds, saved games only make sense with the same list
+    pub fn piece_names(&self) -> Vec<String> {
+        (0..self.id_allocator)
+            .filter_map(|id| self.pieces.get(&PieceKind(id)))
+            .map(|data| data.name.clone())
+            .collect()
+    }
+
+    fn find_by_name(&self, name: &str) -> Option<PieceKind> {
+        self.pieces.iter().find(|(_, data)| data.name == name).map(|(kind, _)| *kind)
+    }
+
+    fn register_piece(&mut self, data: PieceData) -> PieceKind {
+        let piece = PieceKind(self.id_allocator);
+        self.id_allocator += 1;
+        self.pieces.insert(piece, data);
+        piece
+    }
+
+    /// the ways the piece moves, `None` if the registry doesn't know it
+    pub fn get_movement(&self, piece: PieceKind) -> Option<&[Movement]> {
+        self.pieces.get(&piece).map(|data| &data.movements[..])
+    }
+
+    /// material worth of the piece
+    pub fn get_value(&self, piece: PieceKind) -> u32 {
+        self.pieces.get(&piece).map_or(0, |data| data.value)
+    }
+
+    /// letter of the piece in the notation, empty for pawns
+    pub fn get_symbol(&self, piece: PieceKind) -> &str {
+        self.pieces.get(&piece).map_or("", |data| &data.symbol)
+    }
+
+    /// the pieces it can turn into on the last row, the first one is the default
+    pub fn get_promotions(&self, piece: PieceKind) -> &[PieceKind] {
+        self.pieces.get(&piece).map_or(&[], |data| &data.promotions)
+    }
+
+    /// whether losing the piece loses the game
+    pub fn is_royal(&self, piece: PieceKind) -> bool {
+        self.pieces.get(&piece).is_some_and(|data| data.royal)
+    }
+}
@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+use crate::chess_board::{ChessBoard, Piece, Player, Undo};
+use crate::grid::BOARD_SIZE;
+use crate::piece_registry::{PieceKind, PieceRegistry};
+use crate::selection::MoveKind;
+
+/// A case of the board as `(x, y)`, counted from the top left corner which is a8 on a standard board.
+pub type Square = (usize, usize);
+
+/// A move of the piece on `from` to `to`, as found on a given position.
+/// Only boards make them, see [`ChessBoard::legal_moves`] and [`ChessBoard::get_move`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct Move {
+    /// the case the piece leaves
+    pub from: Square,
+    /// the case the piece lands on
+    pub to: Square,
+    /// the piece that moves, as it was before the move
+    pub piece: Piece,
+    /// the opponent piece taken, which isn't on `to` for en passant
+    pub captured: Option<Piece>,
+    /// which side effects the move has
+    pub kind: MoveKind,
+}
+
+impl Move {
+    /// whether an opponent piece leaves the board
+    pub fn is_capture(self) -> bool {
+        self.captured.is_some()
+    }
+
+    /// the piece the moved one turns into
+    pub fn promotion(self) -> Option<PieceKind> {
+        match self.kind {
+            MoveKind::Promotion { into, .. } => Some(into),
+            _ => None,
+        }
+    }
+}
+
+/// a move for each piece a promotion can turn into, any other move alone.
+/// Boards only find the default promotion with the destination, the others are added here
+pub(crate) fn with_promotions(played: Move, promotions: &[PieceKind]) -> impl Iterator<Item = Move> + '_ {
+    let capture = matches!(played.kind, MoveKind::Promotion { capture: true, .. });
+    let promotions = if played.promotion().is_some() { promotions } else { &[] };
+    promotions.is_empty().then_some(played).into_iter()
+        .chain(promotions.iter().map(move |&into| Move { kind: MoveKind::Promotion { into, capture }, ..played }))
+}
+
+/// How a game ended on the board.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum GameResult {
+    /// the side to move has no move and its royal piece is attacked
+    Checkmate {
+        /// the side that gave the mate
+        winner: Player,
+    },
+    /// the side to move has no move but isn't in check
+    Stalemate,
+}
+
+impl GameResult {
+    /// the result as written at the end of a PGN game
+    pub fn score(self) -> &'static str {
+        match self {
+            GameResult::Checkmate { winner: Player::White } => "1-0",
+            GameResult::Checkmate { winner: Player::Black } => "0-1",
+            GameResult::Stalemate => "1/2-1/2",
+        }
+    }
+
+    /// a word for why the game ended
+    pub fn reason(self) -> &'static str {
+        match self {
+            GameResult::Checkmate { .. } => "checkmate",
+            GameResult::Stalemate => "stalemate",
+        }
+    }
+}
+
+/// What engines, notation and network code need from a board, whatever its representation.
+/// The rules of the pieces come from the registry, so every method that moves them takes one.
+pub trait Position {
+    /// what a move changed, to take it back
+    type Undo;
+
+    /// the side to move
+    fn turn(&self) -> Player;
+
+    /// the piece on a case, `None` when it is empty or outside the board
+    fn piece_at(&self, square: Square) -> Option<Piece>;
+
+    /// every move the side to move can play without leaving its royal piece attacked
+    fn legal_moves<'a>(&'a self, registry: &'a PieceRegistry) -> impl Iterator<Item = Move> + 'a;
+
+    /// whether the royal piece of the side to move is attacked
+    fn is_check(&self, registry: &PieceRegistry) -> bool;
+
+    /// a hash of the position, equal positions have equal hashes whatever the moves that led to them
+    fn hash(&self) -> u64;
+
+    /// play a move given by [`Position::legal_moves`] in place, the turn passes to the opponent
+    fn make_move(&mut self, played: Move) -> Self::Undo;
+
+    /// take back the last move made, with the record [`Position::make_move`] gave for it
+    fn unmake_move(&mut self, undo: Self::Undo);
+
+    /// play a move for good
+    fn play(&mut self, played: Move) {
+        self.make_move(played);
+    }
+
+    /// how the game ended, `None` while the side to move still has a move
+    fn result(&self, registry: &PieceRegistry) -> Option<GameResult> {
+        if self.legal_moves(registry).next().is_some() {
+            None
+        } else if self.is_check(registry) {
+            Some(GameResult::Checkmate { winner: self.turn().opponent() })
+        } else {
+            Some(GameResult::Stalemate)
+        }
+    }
+}
+
+impl Position for ChessBoard {
+    type Undo = Undo;
+
+    fn turn(&self) -> Player {
+        self.turn()
+    }
+
+    fn piece_at(&self, (x, y): Square) -> Option<Piece> {
+        if x < BOARD_SIZE && y < BOARD_SIZE { self.get_piece(x, y) } else { None }
+    }
+
+    fn legal_moves<'a>(&'a self, registry: &'a PieceRegistry) -> impl Iterator<Item = Move> + 'a {
+        self.legal_moves(registry)
+    }
+
+    fn is_check(&self, registry: &PieceRegistry) -> bool {
+        self.checked_square(registry).is_some()
+    }
+
+    fn hash(&self) -> u64 {
+        self.hash()
+    }
+
+    fn make_move(&mut self, played: Move) -> Undo {
+        self.make_move(played)
+    }
+
+    fn unmake_move(&mut self, undo: Undo) {
+        self.unmake_move(undo)
+    }
+}
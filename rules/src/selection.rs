@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+use crate::grid::{Grid, BOARD_SIZE};
+use crate::piece_registry::PieceKind;
+
+/// A piece picked on the board, with the cases it can go to.
+pub struct Selection {
+    /// column of the piece
+    pub x: usize,
+    /// row of the piece
+    pub y: usize,
+    /// where the piece can go, and how
+    pub choice: PossibleChoice,
+}
+
+impl Selection {
+    /// the piece on (x, y), with no destination yet
+    pub fn new(x: usize, y: usize) -> Self {
+        Self {
+            x,
+            y,
+            choice: PossibleChoice::new()
+        }
+    }
+}
+
+/// The side effects of a move, found along with its destination.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum MoveKind {
+    /// to an empty case
+    Quiet,
+    /// takes the piece on the destination
+    Capture,
+    /// first move of a piece, it can be taken en passant on the next turn
+    DoubleStep,
+    /// takes the piece that just made a double step past the destination
+    EnPassant,
+    /// jump toward an unmoved friend, which lands on the crossed case
+    Castling,
+    /// the piece reached the last row and turns into another
+    Promotion {
+        /// the piece it turns into
+        into: PieceKind,
+        /// whether the destination held an opponent
+        capture: bool,
+    },
+}
+
+impl MoveKind {
+    /// whether an opponent piece leaves the board
+    pub fn is_capture(self) -> bool {
+        matches!(self, MoveKind::Capture | MoveKind::EnPassant | MoveKind::Promotion { capture: true, .. })
+    }
+
+    /// whether the move does more than going from one case to another
+    pub fn is_special(self) -> bool {
+        matches!(self, MoveKind::EnPassant | MoveKind::Castling | MoveKind::Promotion { .. })
+    }
+}
+
+/// The destinations of a selected piece, each with the kind of move that reaches it.
+pub struct PossibleChoice {
+    grid: Grid<Option<MoveKind>>
+}
+
+impl Default for PossibleChoice {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PossibleChoice {
+    /// no destination at all
+    pub fn new() -> Self {
+        Self {
+            grid: Grid::splat(None)
+        }
+    }
+
+    /// make (x, y) a destination
+    pub fn add(&mut self, x: i32, y: i32, kind: MoveKind) {
+        *self.grid.get_mut(x as usize, y as usize) = Some(kind);
+    }
+
+    /// whether (x, y) is a destination
+    pub fn is_available(&self, x: usize, y: usize) -> bool {
+        self.grid.get(x, y).is_some()
+    }
+
+    /// how the piece gets to (x, y), if it can
+    pub fn kind(&self, x: usize, y: usize) -> Option<MoveKind> {
+        *self.grid.get(x, y)
+    }
+
+    /// every case of the board with the way to get there, row by row from the top
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, &Option<MoveKind>)> {
+        self.grid.iter()
+    }
+
+    /// only the destinations, in the same order as [`PossibleChoice::iter`]
+    pub fn into_destinations(self) -> impl Iterator<Item = (usize, usize, MoveKind)> {
+        (0..BOARD_SIZE * BOARD_SIZE).filter_map(move |i| {
+            let (x, y) = (i % BOARD_SIZE, i / BOARD_SIZE);
+            self.kind(x, y).map(|kind| (x, y, kind))
+        })
+    }
+}
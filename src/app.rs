@@ -6,14 +6,23 @@ use vello::peniko::Color;
 use vello::util::{RenderContext, RenderSurface};
 use winit::application::ApplicationHandler;
 use winit::dpi::{LogicalSize, PhysicalPosition};
-use winit::event::{ElementState, MouseButton, WindowEvent};
+use winit::event::{ElementState, MouseButton, MouseScrollDelta, WindowEvent};
 use winit::event_loop::{ActiveEventLoop, ControlFlow};
-use winit::keyboard::{Key, NamedKey};
+use winit::keyboard::{Key, ModifiersState, NamedKey};
 use winit::window::Window;
 
 pub trait LogicHandler {
     fn on_mouse_click(&mut self, x: f64, y: f64);
+    fn on_right_press(&mut self, x: f64, y: f64);
+    fn on_right_release(&mut self, x: f64, y: f64, modifiers: ModifiersState);
+    /// `rows` is positive when scrolling up
+    fn on_scroll(&mut self, x: f64, y: f64, rows: f64);
     fn on_exit_press(&mut self);
+    fn on_key_press(&mut self, key: &Key);
+    /// the characters produced by a key press, sent after `on_key_press`
+    fn on_text_input(&mut self, text: &str);
+    /// the app may be closed without further notice after this, anything worth keeping must be saved
+    fn on_suspend(&mut self);
     fn draw(&mut self, scene: &mut Scene, duration: Duration);
     fn surface_resize(&mut self, width: u32, height: u32);
 }
@@ -23,7 +32,7 @@ pub struct ActiveRenderState<'s> {
 }
 
 enum RenderState<'s> {
-    Active(ActiveRenderState<'s>),
+    Active(Box<ActiveRenderState<'s>>),
     // Cache a window so that it can be reused when the app is resumed after being suspended
     Suspended(Option<Arc<Window>>),
 }
@@ -45,10 +54,13 @@ pub struct SimpleVelloApp<'s, T: LogicHandler> {
     scene: Scene,
     last_frame_time: Instant,
     last_cursor_pos: PhysicalPosition<f64>,
+    modifiers: ModifiersState,
     logic_handler: T
 }
 
 impl<'s, T: LogicHandler> SimpleVelloApp<'s, T> {
+    const PIXELS_PER_ROW: f64 = 40.0; //converts touchpad scrolling into rows
+
     pub fn new(logic_handler: T) -> Self {
         Self {
             context: RenderContext::new(),
@@ -57,6 +69,7 @@ impl<'s, T: LogicHandler> SimpleVelloApp<'s, T> {
             scene: Default::default(),
             last_frame_time: Instant::now(),
             last_cursor_pos: Default::default(),
+            modifiers: Default::default(),
             logic_handler
         }
     }
@@ -114,7 +127,9 @@ impl<'s, T: LogicHandler> ApplicationHandler for SimpleVelloApp<'s, T> {
             .get_or_insert_with(|| Self::create_vello_renderer(&self.context, &surface));
 
         // Save the Window and Surface to a state variable
-        self.state = RenderState::Active(ActiveRenderState { window, surface });
+        self.state = RenderState::Active(Box::new(ActiveRenderState { window, surface }));
+        // the time spent in the background doesn't count as a frame
+        self.last_frame_time = Instant::now();
 
         event_loop.set_control_flow(ControlFlow::Poll);
     }
@@ -136,20 +151,36 @@ impl<'s, T: LogicHandler> ApplicationHandler for SimpleVelloApp<'s, T> {
         };
 
         match event {
-            WindowEvent::CloseRequested => event_loop.exit(),
+            WindowEvent::CloseRequested => {
+                self.logic_handler.on_suspend();
+                event_loop.exit();
+            }
             WindowEvent::CursorMoved { position, .. } => {
                 self.last_cursor_pos = position;
             }
-            WindowEvent::MouseInput { state, button, .. } => {
-                if button == MouseButton::Left && state == ElementState::Pressed {
-                    self.logic_handler.on_mouse_click(self.last_cursor_pos.x, self.last_cursor_pos.y);
-                }
+            WindowEvent::MouseInput { state: ElementState::Pressed, button: MouseButton::Left, .. } => {
+                self.logic_handler.on_mouse_click(self.last_cursor_pos.x, self.last_cursor_pos.y);
             }
-            WindowEvent::Touch(touch) => {
-                if touch.phase == winit::event::TouchPhase::Ended {
-                    self.logic_handler.on_mouse_click(touch.location.x, touch.location.y);
+            WindowEvent::MouseInput { state, button: MouseButton::Right, .. } => {
+                let position = self.last_cursor_pos;
+                match state {
+                    ElementState::Pressed => self.logic_handler.on_right_press(position.x, position.y),
+                    ElementState::Released => self.logic_handler.on_right_release(position.x, position.y, self.modifiers),
                 }
             }
+            WindowEvent::MouseWheel { delta, .. } => {
+                let rows = match delta {
+                    MouseScrollDelta::LineDelta(_, rows) => rows as f64,
+                    MouseScrollDelta::PixelDelta(position) => position.y / Self::PIXELS_PER_ROW,
+                };
+                self.logic_handler.on_scroll(self.last_cursor_pos.x, self.last_cursor_pos.y, rows);
+            }
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers.state();
+            }
+            WindowEvent::Touch(touch) if touch.phase == winit::event::TouchPhase::Ended => {
+                self.logic_handler.on_mouse_click(touch.location.x, touch.location.y);
+            }
             WindowEvent::KeyboardInput { event, ..} => {
                 debug!("keyboard input : {:?}", event.logical_key);
                 if event.logical_key == Key::Named(NamedKey::Escape) && event.state == ElementState::Released {
@@ -161,6 +192,12 @@ impl<'s, T: LogicHandler> ApplicationHandler for SimpleVelloApp<'s, T> {
                 if event.logical_key == Key::Named(NamedKey::BrowserBack) && event.state == ElementState::Released {
                     self.logic_handler.on_exit_press()
                 }
+                if event.state == ElementState::Pressed {
+                    self.logic_handler.on_key_press(&event.logical_key);
+                    if let Some(text) = &event.text {
+                        self.logic_handler.on_text_input(text);
+                    }
+                }
             }
             WindowEvent::Resized(size) => {
                 self.context
@@ -225,6 +262,7 @@ impl<'s, T: LogicHandler> ApplicationHandler for SimpleVelloApp<'s, T> {
     }
 
     fn suspended(&mut self, event_loop: &ActiveEventLoop) {
+        self.logic_handler.on_suspend();
         if let RenderState::Active(state) = &self.state {
             self.state = RenderState::Suspended(Some(state.window.clone()));
         }
@@ -0,0 +1,82 @@
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+use chess_game::game::{AnimationOptions, Diagram, Player};
+use chess_game::headless::HeadlessRenderer;
+
+const USAGE: &str = "usage: render_game --pgn FILE [--size PIXELS] [--delay MS] [--final-delay MS] [--black] [--theme NAME] [--no-highlights] [--no-captions] [--config DIR] [--output FILE.gif|FILE.png]";
+
+/// Render every position of the main line of a PGN game into an animated gif,
+/// or an animated png when the output file ends with `.png`.
+fn main() -> ExitCode {
+    let mut pgn = None;
+    let mut options = AnimationOptions::default();
+    let mut orientation = Player::White;
+    let mut theme = None;
+    let mut config = PathBuf::from("config");
+    let mut output = PathBuf::from("game.gif");
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{arg} needs a value"));
+        let milliseconds = |value: String| value.parse().map(Duration::from_millis).map_err(|error| format!("bad delay: {error}"));
+        let parsed = match arg.as_str() {
+            "--pgn" => value().map(|value| pgn = Some(PathBuf::from(value))),
+            "--size" => value().and_then(|value| value.parse().map(|value| options.size = value).map_err(|error| format!("bad size: {error}"))),
+            "--delay" => value().and_then(milliseconds).map(|delay| options.delay = delay),
+            "--final-delay" => value().and_then(milliseconds).map(|delay| options.final_delay = delay),
+            "--black" => {
+                orientation = Player::Black;
+                Ok(())
+            }
+            "--theme" => value().map(|value| theme = Some(value)),
+            "--no-highlights" => {
+                options.highlights = false;
+                Ok(())
+            }
+            "--no-captions" => {
+                options.captions = false;
+                Ok(())
+            }
+            "--config" => value().map(|value| config = PathBuf::from(value)),
+            "--output" => value().map(|value| output = PathBuf::from(value)),
+            _ => Err(format!("unknown argument {arg}")),
+        };
+        if let Err(error) = parsed {
+            eprintln!("{error}\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    }
+    let Some(pgn) = pgn else {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    };
+
+    let mut diagram = match Diagram::load(&config, theme.as_deref()) {
+        Ok(diagram) => diagram,
+        Err(error) => {
+            eprintln!("{error}");
+            return ExitCode::FAILURE;
+        }
+    };
+    diagram.set_orientation(orientation);
+    match write_animation(&diagram, &pgn, &options, &output) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn write_animation(diagram: &Diagram, pgn: &Path, options: &AnimationOptions, output: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let animation = diagram.animate_pgn(&std::fs::read_to_string(pgn)?, options)?;
+    let mut renderer = HeadlessRenderer::new()?;
+    let encoded = if output.extension().is_some_and(|extension| extension == "png") {
+        renderer.render_apng(&animation)?
+    } else {
+        renderer.render_gif(&animation)?
+    };
+    std::fs::write(output, encoded)?;
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use vello::peniko::Color;
+use vello::Scene;
+use chess_game::game::{Diagram, Player, SvgOptions, STARTING_POSITION};
+use chess_game::headless::{encode_png, HeadlessRenderer};
+
+const USAGE: &str = "usage: render_position [--fen FEN] [--size PIXELS] [--black] [--theme NAME] [--annotations \"[%cal Ge2e4][%csl Rd5]\"] [--config DIR] [--output FILE.png|FILE.svg]";
+
+/// Render a position to a png without opening a window, on the CPU when there is no GPU,
+/// or write it as an svg document when the output file ends with `.svg`.
+fn main() -> ExitCode {
+    let mut fen = STARTING_POSITION.to_string();
+    let mut size = 800;
+    let mut orientation = Player::White;
+    let mut theme = None;
+    let mut annotations = String::new();
+    let mut config = PathBuf::from("config");
+    let mut output = PathBuf::from("position.png");
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{arg} needs a value"));
+        let parsed = match arg.as_str() {
+            "--fen" => value().map(|value| fen = value),
+            "--size" => value().and_then(|value| value.parse().map(|value| size = value).map_err(|error| format!("bad size: {error}"))),
+            "--black" => {
+                orientation = Player::Black;
+                Ok(())
+            }
+            "--theme" => value().map(|value| theme = Some(value)),
+            "--annotations" => value().map(|value| annotations = value),
+            "--config" => value().map(|value| config = PathBuf::from(value)),
+            "--output" => value().map(|value| output = PathBuf::from(value)),
+            _ => Err(format!("unknown argument {arg}")),
+        };
+        if let Err(error) = parsed {
+            eprintln!("{error}\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    }
+
+    let mut diagram = match Diagram::load(&config, theme.as_deref()) {
+        Ok(diagram) => diagram,
+        Err(error) => {
+            eprintln!("{error}");
+            return ExitCode::FAILURE;
+        }
+    };
+    diagram.set_orientation(orientation);
+    diagram.set_annotations(&annotations);
+    let written = if output.extension().is_some_and(|extension| extension == "svg") {
+        write_svg(&diagram, &fen, &output)
+    } else {
+        write_png(&diagram, &fen, size, &output)
+    };
+    match written {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn write_svg(diagram: &Diagram, fen: &str, output: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::write(output, diagram.svg_fen(fen, SvgOptions::default())?)?;
+    Ok(())
+}
+
+fn write_png(diagram: &Diagram, fen: &str, size: u32, output: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let mut scene = Scene::new();
+    diagram.draw_fen(fen, size as f64 / Diagram::SIZE, &mut scene)?;
+
+    let mut renderer = HeadlessRenderer::new()?;
+    let pixels = renderer.render(&scene, size, size, Color::TRANSPARENT)?;
+    std::fs::write(output, encode_png(&pixels, size, size)?)?;
+    Ok(())
+}
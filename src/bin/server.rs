@@ -0,0 +1,38 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+use chess_game::game::Server;
+
+const USAGE: &str = "usage: server [--address HOST:PORT] [--config DIR]";
+
+/// Host games for clients on the network, without any window or graphics.
+fn main() -> ExitCode {
+    env_logger::init();
+    let mut address = "0.0.0.0:7879".to_string();
+    let mut config = PathBuf::from("config");
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{arg} needs a value"));
+        let parsed = match arg.as_str() {
+            "--address" => value().map(|value| address = value),
+            "--config" => value().map(|value| config = PathBuf::from(value)),
+            _ => Err(format!("unknown argument {arg}")),
+        };
+        if let Err(error) = parsed {
+            eprintln!("{error}\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    }
+
+    let served = Server::bind(&address, &config).and_then(|server| {
+        println!("serving games on {}", server.local_addr()?);
+        server.run()
+    });
+    match served {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{error}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "graphics")]
+use vello::peniko::Color;
+use crate::game::notation::{parse_coordinates, parse_square, square_name};
+
+/// The colors an annotation can be drawn with, named after their PGN letter.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Brush {
+    Green,
+    Red,
+    Blue,
+    Yellow,
+}
+
+impl Brush {
+    pub fn letter(self) -> char {
+        match self {
+            Brush::Green => 'G',
+            Brush::Red => 'R',
+            Brush::Blue => 'B',
+            Brush::Yellow => 'Y',
+        }
+    }
+
+    pub fn from_letter(letter: char) -> Option<Self> {
+        [Brush::Green, Brush::Red, Brush::Blue, Brush::Yellow].into_iter().find(|brush| brush.letter() == letter)
+    }
+
+    #[cfg(feature = "graphics")]
+    pub fn color(self) -> Color {
+        match self {
+            Brush::Green => Color::rgba8(21, 120, 27, 170),
+            Brush::Red => Color::rgba8(136, 32, 32, 170),
+            Brush::Blue => Color::rgba8(0, 48, 136, 170),
+            Brush::Yellow => Color::rgba8(230, 143, 0, 170),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Arrow {
+    pub brush: Brush,
+    pub from: (usize, usize),
+    pub to: (usize, usize),
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Circle {
+    pub brush: Brush,
+    pub square: (usize, usize),
+}
+
+/// Arrows and circled squares drawn by the user on a position.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Annotations {
+    pub arrows: Vec<Arrow>,
+    pub circles: Vec<Circle>,
+}
+
+impl Annotations {
+    pub fn is_empty(&self) -> bool {
+        self.arrows.is_empty() && self.circles.is_empty()
+    }
+
+    /// drawing the same arrow again erases it, drawing it with another brush recolors it
+    pub fn toggle_arrow(&mut self, arrow: Arrow) {
+        match self.arrows.iter().position(|other| (other.from, other.to) == (arrow.from, arrow.to)) {
+            Some(index) if self.arrows[index].brush == arrow.brush => { self.arrows.remove(index); }
+            Some(index) => self.arrows[index].brush = arrow.brush,
+            None => self.arrows.push(arrow),
+        }
+    }
+
+    pub fn toggle_circle(&mut self, circle: Circle) {
+        match self.circles.iter().position(|other| other.square == circle.square) {
+            Some(index) if self.circles[index].brush == circle.brush => { self.circles.remove(index); }
+            Some(index) => self.circles[index].brush = circle.brush,
+            None => self.circles.push(circle),
+        }
+    }
+
+    /// the `[%csl]` and `[%cal]` commands understood by most PGN viewers
+    pub fn to_pgn_commands(&self) -> String {
+        let mut commands = String::new();
+        if !self.circles.is_empty() {
+            let circles: Vec<_> = self.circles.iter()
+                .map(|circle| format!("{}{}", circle.brush.letter(), square_name(circle.square.0, circle.square.1)))
+                .collect();
+            commands += &format!("[%csl {}]", circles.join(","));
+        }
+        if !self.arrows.is_empty() {
+            let arrows: Vec<_> = self.arrows.iter()
+                .map(|arrow| format!("{}{}{}", arrow.brush.letter(), square_name(arrow.from.0, arrow.from.1), square_name(arrow.to.0, arrow.to.1)))
+                .collect();
+            commands += &format!("[%cal {}]", arrows.join(","));
+        }
+        commands
+    }
+
+    /// read back the `[%csl]` and `[%cal]` commands found in a comment, ignoring anything else
+    pub fn from_pgn_commands(comment: &str) -> Self {
+        let mut annotations = Self::default();
+        let items = |command: &str| -> Vec<(Brush, String)> {
+            let Some(start) = comment.find(command) else {
+                return Vec::new();
+            };
+            let arguments = &comment[start + command.len()..];
+            let arguments = &arguments[..arguments.find(']').unwrap_or(arguments.len())];
+            arguments.split(',')
+                .filter_map(|item| {
+                    let item = item.trim();
+                    let brush = Brush::from_letter(item.chars().next()?)?;
+                    Some((brush, item[1..].to_string()))
+                })
+                .collect()
+        };
+        for (brush, square) in items("[%csl ") {
+            if let Some(square) = parse_square(&square) {
+                annotations.circles.push(Circle { brush, square });
+            }
+        }
+        // an arrow from a case to itself has no direction to point in
+        for (brush, squares) in items("[%cal ") {
+            if let Some((from, to)) = parse_coordinates(&squares).filter(|(from, to)| from != to) {
+                annotations.arrows.push(Arrow { brush, from, to });
+            }
+        }
+        annotations
+    }
+}
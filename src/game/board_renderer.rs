@@ -1,31 +1,57 @@
-use vello::kurbo::{Affine, Circle, Rect, Stroke};
+use vello::kurbo::{Affine, BezPath, Cap, Circle, Line, Point, Rect, RoundedRect, Stroke, Vec2};
 use vello::peniko::{Color, Fill};
 use vello::Scene;
-use crate::game::Selection;
-use crate::game::chess_board::ChessBoard;
+use crate::game::selection::Selection;
+use crate::game::annotation::{Annotations, Arrow};
+use crate::game::chess_board::{ChessBoard, Player};
 use crate::game::grid::BOARD_SIZE;
+use crate::game::notation::{file_name, rank_name};
 use crate::game::piece_registry::PieceRegistry;
+use crate::game::sprites::PieceSprites;
+use crate::game::text::{Align, TextRenderer};
+use crate::game::theme::Theme;
+
+mod svg;
+
+pub use svg::SvgOptions;
 
 pub struct BoardRenderer {
     board_scene: Scene,
+    theme: Theme,
+    text: TextRenderer,
+    orientation: Player, // the player whose pieces are drawn at the bottom of the board
 }
 
 impl BoardRenderer {
-    pub fn new() -> Self {
-        let tile_colors =  [Color::rgb(0.9, 0.9, 0.8), Color::rgb(0.2, 0.3, 0.5)];
+    pub fn new(theme: Theme) -> Self {
         Self {
-            board_scene: Self::create_board(tile_colors),
+            board_scene: Self::create_board(Self::tile_colors(&theme)),
+            theme,
+            text: TextRenderer::new(),
+            orientation: Player::White,
         }
     }
 
 
     pub const CELL_SIZE: f64 = 100.0;
     pub const BOARD_SIZE: f64 = Self::CELL_SIZE * BOARD_SIZE as f64;
+    const LABEL_SIZE: f32 = 18.0;
+    const LABEL_MARGIN: f64 = 5.0;
+    const ARROW_WIDTH: f64 = Self::CELL_SIZE * 0.15;
+
+    fn tile_colors(theme: &Theme) -> [Color; 2] {
+        [theme.light_square, theme.dark_square]
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.board_scene = Self::create_board(Self::tile_colors(&theme));
+        self.theme = theme;
+    }
 
     fn create_board(tile_colors: [Color; 2]) -> Scene {
         let mut scene = Scene::new();
-        for y in 0..8 {
-            for x in 0..8 {
+        for y in 0..BOARD_SIZE {
+            for x in 0..BOARD_SIZE {
                 let color = tile_colors[ (x + y) % 2];
                 let x = x as f64;
                 let y = y as f64;
@@ -37,17 +63,95 @@ impl BoardRenderer {
         scene
     }
 
+    pub fn orientation(&self) -> Player {
+        self.orientation
+    }
+
+    pub fn set_orientation(&mut self, orientation: Player) {
+        self.orientation = orientation;
+    }
+
+    /// the grid is stored with Black on the top rows, so viewing it from Black's side is a half turn
+    fn view_cell(&self, x: usize, y: usize) -> (usize, usize) {
+        match self.orientation {
+            Player::White => (x, y),
+            Player::Black => (BOARD_SIZE - 1 - x, BOARD_SIZE - 1 - y),
+        }
+    }
+
+    fn cell_transform(&self, x: usize, y: usize) -> Affine {
+        let (x, y) = self.view_cell(x, y);
+        Affine::translate((x as f64 * Self::CELL_SIZE, y as f64 * Self::CELL_SIZE))
+    }
+
+    fn cell_center(&self, x: usize, y: usize) -> Point {
+        let (x, y) = self.view_cell(x, y);
+        Point::new((x as f64 + 0.5) * Self::CELL_SIZE, (y as f64 + 0.5) * Self::CELL_SIZE)
+    }
+
+    /// convert a point in board space into the grid cell under it, taking the orientation into account
+    pub fn cell_at(&self, point: Point) -> Option<(usize, usize)> {
+        let rect = Rect::new(0.0, 0.0, Self::BOARD_SIZE, Self::BOARD_SIZE);
+        if !rect.contains(point) {
+            return None;
+        }
+        let x = (point.x / Self::CELL_SIZE) as usize;
+        let y = (point.y / Self::CELL_SIZE) as usize;
+        // the view mapping is its own inverse
+        Some(self.view_cell(x, y))
+    }
+
     pub fn draw_board(&self, scene: &mut Scene) {
-        scene.append(&self.board_scene, None)
+        scene.append(&self.board_scene, None);
+        self.draw_coordinates(scene);
+    }
+
+    /// files along the bottom row and ranks along the left column, written inside the squares
+    fn draw_coordinates(&self, scene: &mut Scene) {
+        let last = BOARD_SIZE - 1;
+        // a label is written with the color of the other kind of square so it stays readable
+        let tile_colors = Self::tile_colors(&self.theme);
+        let label_color = |view_x: usize, view_y: usize| tile_colors[(view_x + view_y + 1) % 2];
+
+        for view_x in 0..BOARD_SIZE {
+            let (x, _) = self.view_cell(view_x, last);
+            let position = ((view_x + 1) as f64 * Self::CELL_SIZE - Self::LABEL_MARGIN, Self::BOARD_SIZE - Self::LABEL_MARGIN);
+            let label = file_name(x).to_string();
+            self.text.draw(scene, &label, Self::LABEL_SIZE, Align::End, label_color(view_x, last), Affine::translate(position));
+        }
+
+        let baseline = Self::LABEL_MARGIN + self.text.cap_height(Self::LABEL_SIZE);
+        for view_y in 0..BOARD_SIZE {
+            let (_, y) = self.view_cell(0, view_y);
+            let position = (Self::LABEL_MARGIN, view_y as f64 * Self::CELL_SIZE + baseline);
+            self.text.draw(scene, &rank_name(y), Self::LABEL_SIZE, Align::Start, label_color(0, view_y), Affine::translate(position));
+        }
+    }
+
+    fn fill_cell(&self, x: usize, y: usize, color: Color, scene: &mut Scene) {
+        let rect = Rect::new(0.0, 0.0, Self::CELL_SIZE, Self::CELL_SIZE);
+        scene.fill(Fill::NonZero, self.cell_transform(x, y), color, None, &rect);
+    }
+
+    /// mark the last move and a royal piece in check, drawn under the pieces
+    pub fn draw_highlights(&self, board: &ChessBoard, registry: &PieceRegistry, scene: &mut Scene) {
+        if let Some(((from_x, from_y), (x, y))) = board.last_move() {
+            self.fill_cell(from_x, from_y, self.theme.last_move, scene);
+            self.fill_cell(x, y, self.theme.last_move, scene);
+        }
+
+        if let Some((x, y)) = board.checked_square(registry) {
+            let center = Self::CELL_SIZE / 2.0;
+            let circle = Circle::new((center, center), center);
+            scene.fill(Fill::NonZero, self.cell_transform(x, y), self.theme.check, None, &circle);
+        }
     }
 
-    pub fn draw_pieces(&self, board: &ChessBoard, registry: &PieceRegistry, scene: &mut Scene) {
+    pub fn draw_pieces(&self, board: &ChessBoard, sprites: &PieceSprites, scene: &mut Scene) {
         for (x, y, piece) in board.iter() {
             if let Some(piece) = piece {
-                let x = x as f64;
-                let y = y as f64;
-                let affine = Affine::translate((x * Self::CELL_SIZE, y * Self::CELL_SIZE));
-                scene.append(registry.get_sprite(piece.piece_kind, piece.player), Some(affine));
+                let affine = self.cell_transform(x, y);
+                scene.append(sprites.get_sprite(piece.piece_kind, piece.player), Some(affine));
             }
         }
     }
@@ -57,28 +161,70 @@ impl BoardRenderer {
 
             let stroke = Stroke::new(6.0);
 
-            let color = Color::RED;
+            let color = self.theme.selection;
             let center = Self::CELL_SIZE /2.0;
             let radius = center - 3.0;
-            let x = selection.x as f64;
-            let y = selection.y as f64;
-            let affine = Affine::translate((x * Self::CELL_SIZE, y * Self::CELL_SIZE));
+            let affine = self.cell_transform(selection.x, selection.y);
             let circle = Circle::new((center, center), radius);
             scene.stroke(&stroke, affine, color, None, &circle);
 
-            for (x, y, available) in selection.choice.iter() {
-                let circle = Circle::new((50.0, 50.0), 15.0);
-                let color = Color::LIME; // todo: use a light opacity
+            let dot = Circle::new((center, center), 15.0);
+            let ring = Circle::new((center, center), center - 6.0);
+            let marker = RoundedRect::new(8.0, 8.0, Self::CELL_SIZE - 8.0, Self::CELL_SIZE - 8.0, 12.0);
+            let color = self.theme.move_hint;
 
-                if *available {
-                    let x = x as f64;
-                    let y = y as f64;
-
-                    let affine = Affine::translate((x * Self::CELL_SIZE, y * Self::CELL_SIZE));
-                    scene.fill(Fill::NonZero, affine, &color, None, &circle);
+            for (x, y, kind) in selection.choice.iter() {
+                let Some(kind) = kind else {
+                    continue;
+                };
+                let affine = self.cell_transform(x, y);
+                if kind.is_special() {
+                    scene.stroke(&Stroke::new(8.0), affine, color, None, &marker);
+                } else if kind.is_capture() {
+                    scene.stroke(&Stroke::new(8.0), affine, color, None, &ring);
+                } else {
+                    scene.fill(Fill::NonZero, affine, color, None, &dot);
                 }
             }
         }
     }
-}
 
+    /// the square targeted by the keyboard
+    pub fn draw_cursor(&self, x: usize, y: usize, scene: &mut Scene) {
+        let frame = Rect::new(4.0, 4.0, Self::CELL_SIZE - 4.0, Self::CELL_SIZE - 4.0);
+        scene.stroke(&Stroke::new(8.0), self.cell_transform(x, y), self.theme.selection, None, &frame);
+    }
+
+    /// circled squares and arrows drawn by the user, on top of everything else
+    pub fn draw_annotations(&self, annotations: &Annotations, scene: &mut Scene) {
+        for circle in &annotations.circles {
+            let center = self.cell_center(circle.square.0, circle.square.1);
+            let shape = Circle::new(center, Self::CELL_SIZE / 2.0 - 5.0);
+            scene.stroke(&Stroke::new(8.0), Affine::IDENTITY, circle.brush.color(), None, &shape);
+        }
+
+        for arrow in &annotations.arrows {
+            let (shaft, head) = self.arrow_shapes(arrow);
+            scene.stroke(&Stroke::new(Self::ARROW_WIDTH).with_start_cap(Cap::Butt).with_end_cap(Cap::Butt), Affine::IDENTITY, arrow.brush.color(), None, &shaft);
+            scene.fill(Fill::NonZero, Affine::IDENTITY, arrow.brush.color(), None, &head);
+        }
+    }
+
+    /// the line stroked for the shaft of an arrow, and its filled head
+    fn arrow_shapes(&self, arrow: &Arrow) -> (Line, BezPath) {
+        let head_length = Self::CELL_SIZE * 0.4;
+        let head_width = Self::CELL_SIZE * 0.25;
+        let start = self.cell_center(arrow.from.0, arrow.from.1);
+        let tip = self.cell_center(arrow.to.0, arrow.to.1);
+        let direction = (tip - start).normalize();
+        let normal = Vec2::new(-direction.y, direction.x);
+        let base = tip - direction * head_length;
+
+        let mut head = BezPath::new();
+        head.move_to(tip);
+        head.line_to(base + normal * head_width);
+        head.line_to(base - normal * head_width);
+        head.close_path();
+        (Line::new(start, base), head)
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use vello::kurbo::Shape;
+use vello::peniko::Color;
+use vello_svg::usvg;
+use crate::game::annotation::Annotations;
+use crate::game::board_renderer::BoardRenderer;
+use crate::game::chess_board::{ChessBoard, Piece, Player};
+use crate::game::grid::BOARD_SIZE;
+use crate::game::notation::{file_name, rank_name};
+use crate::game::piece_registry::PieceRegistry;
+use crate::game::sprites::PieceSprites;
+
+/// What goes on an svg diagram besides the squares and the pieces.
+#[derive(Debug, Clone, Copy)]
+pub struct SvgOptions {
+    pub coordinates: bool,
+    pub highlights: bool, //last move and check
+    pub annotations: bool,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            coordinates: true,
+            highlights: true,
+            annotations: true,
+        }
+    }
+}
+
+/// a color as svg presentation attributes, the alpha goes in a separate opacity since not every viewer reads `#rrggbbaa`
+fn paint(attribute: &str, color: Color) -> String {
+    let Color { r, g, b, a } = color;
+    format!(r##"{attribute}="#{r:02x}{g:02x}{b:02x}" {attribute}-opacity="{:.3}""##, a as f64 / 255.0)
+}
+
+/// pieces of the same kind and player share their definition
+fn piece_id(piece: Piece) -> String {
+    let color = match piece.player {
+        Player::White => 'w',
+        Player::Black => 'b',
+    };
+    format!("piece-{}{color}", piece.piece_kind.index())
+}
+
+/// the inside of a piece svg, cleaned up by usvg with its ids prefixed so several pieces can share a document
+fn piece_body(svg: &str, prefix: &str) -> Option<(String, f64)> {
+    let tree = usvg::Tree::from_str(svg, &usvg::Options::default()).ok()?;
+    let text = tree.to_string(&usvg::WriteOptions {
+        id_prefix: Some(prefix.to_string()),
+        ..Default::default()
+    });
+    let start = text.find("<svg")?;
+    let start = start + text[start..].find('>')? + 1;
+    let end = text.rfind("</svg>")?;
+    Some((text[start..end].to_string(), tree.size().width() as f64))
+}
+
+impl BoardRenderer {
+    /// A standalone svg document of the position, laid out like the board on screen.
+    /// The pieces are the svg files of the config, embedded once each and reused.
+    pub fn svg_document(&self, board: &ChessBoard, registry: &PieceRegistry, sprites: &PieceSprites, annotations: &Annotations, options: SvgOptions) -> String {
+        let size = Self::BOARD_SIZE;
+        let mut svg = format!(r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="{size}" height="{size}" viewBox="0 0 {size} {size}">"#);
+        svg.push('\n');
+
+        let mut pieces = BTreeMap::new();
+        for (_, _, piece) in board.iter() {
+            if let Some(piece) = piece {
+                pieces.entry(piece_id(*piece)).or_insert((piece.piece_kind, piece.player));
+            }
+        }
+        svg += "<defs>\n";
+        for (id, (kind, player)) in &pieces {
+            match piece_body(sprites.get_svg(*kind, *player), &format!("{id}-")) {
+                Some((body, width)) => {
+                    let scale = Self::CELL_SIZE / width;
+                    let _ = writeln!(svg, r#"<g id="{id}" transform="scale({scale})">{body}</g>"#);
+                }
+                None => log::warn!("couldn't embed the sprite of {id}"),
+            }
+        }
+        svg += "</defs>\n";
+
+        let tile_colors = Self::tile_colors(&self.theme);
+        for y in 0..BOARD_SIZE {
+            for x in 0..BOARD_SIZE {
+                let (left, top) = (x as f64 * Self::CELL_SIZE, y as f64 * Self::CELL_SIZE);
+                let _ = writeln!(svg, r#"<rect x="{left}" y="{top}" width="{}" height="{}" {}/>"#, Self::CELL_SIZE, Self::CELL_SIZE, paint("fill", tile_colors[(x + y) % 2]));
+            }
+        }
+
+        if options.coordinates {
+            self.svg_coordinates(&mut svg);
+        }
+
+        if options.highlights {
+            if let Some(((from_x, from_y), (x, y))) = board.last_move() {
+                for (x, y) in [(from_x, from_y), (x, y)] {
+                    let (x, y) = self.view_cell(x, y);
+                    let (left, top) = (x as f64 * Self::CELL_SIZE, y as f64 * Self::CELL_SIZE);
+                    let _ = writeln!(svg, r#"<rect x="{left}" y="{top}" width="{}" height="{}" {}/>"#, Self::CELL_SIZE, Self::CELL_SIZE, paint("fill", self.theme.last_move));
+                }
+            }
+            if let Some((x, y)) = board.checked_square(registry) {
+                let center = self.cell_center(x, y);
+                let _ = writeln!(svg, r#"<circle cx="{}" cy="{}" r="{}" {}/>"#, center.x, center.y, Self::CELL_SIZE / 2.0, paint("fill", self.theme.check));
+            }
+        }
+
+        for (x, y, piece) in board.iter() {
+            if let Some(piece) = piece {
+                let (x, y) = self.view_cell(x, y);
+                // xlink:href for the viewers that only know SVG 1.1
+                let (id, x, y) = (piece_id(*piece), x as f64 * Self::CELL_SIZE, y as f64 * Self::CELL_SIZE);
+                let _ = writeln!(svg, r##"<use href="#{id}" xlink:href="#{id}" x="{x}" y="{y}"/>"##);
+            }
+        }
+
+        if options.annotations {
+            for circle in &annotations.circles {
+                let center = self.cell_center(circle.square.0, circle.square.1);
+                let _ = writeln!(svg, r#"<circle cx="{}" cy="{}" r="{}" fill="none" stroke-width="8" {}/>"#, center.x, center.y, Self::CELL_SIZE / 2.0 - 5.0, paint("stroke", circle.brush.color()));
+            }
+            for arrow in &annotations.arrows {
+                let (shaft, head) = self.arrow_shapes(arrow);
+                let color = arrow.brush.color();
+                let _ = writeln!(svg, r#"<path d="{}" fill="none" stroke-width="{}" {}/>"#, shaft.to_path(0.1).to_svg(), Self::ARROW_WIDTH, paint("stroke", color));
+                let _ = writeln!(svg, r#"<path d="{}" {}/>"#, head.to_svg(), paint("fill", color));
+            }
+        }
+
+        svg += "</svg>\n";
+        svg
+    }
+
+    /// the same labels as `draw_coordinates`, as svg text
+    fn svg_coordinates(&self, svg: &mut String) {
+        let last = BOARD_SIZE - 1;
+        let tile_colors = Self::tile_colors(&self.theme);
+        let label_color = |view_x: usize, view_y: usize| tile_colors[(view_x + view_y + 1) % 2];
+        let font = format!(r#"font-family="Roboto, sans-serif" font-size="{}""#, Self::LABEL_SIZE);
+
+        for view_x in 0..BOARD_SIZE {
+            let (x, _) = self.view_cell(view_x, last);
+            let position = ((view_x + 1) as f64 * Self::CELL_SIZE - Self::LABEL_MARGIN, Self::BOARD_SIZE - Self::LABEL_MARGIN);
+            let _ = writeln!(svg, r#"<text x="{}" y="{}" text-anchor="end" {font} {}>{}</text>"#, position.0, position.1, paint("fill", label_color(view_x, last)), file_name(x));
+        }
+
+        let baseline = Self::LABEL_MARGIN + self.text.cap_height(Self::LABEL_SIZE);
+        for view_y in 0..BOARD_SIZE {
+            let (_, y) = self.view_cell(0, view_y);
+            let position = (Self::LABEL_MARGIN, view_y as f64 * Self::CELL_SIZE + baseline);
+            let _ = writeln!(svg, r#"<text x="{}" y="{}" {font} {}>{}</text>"#, position.0, position.1, paint("fill", label_color(0, view_y)), rank_name(y));
+        }
+    }
+}
@@ -0,0 +1,781 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+use vello::kurbo::Point;
+use vello::Scene;
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+use crate::app::LogicHandler;
+#[cfg(not(target_os = "android"))]
+use crate::headless::HeadlessRenderer;
+use crate::headless::Animation;
+use crate::game::annotation::{Arrow, Brush, Circle};
+use crate::game::board_renderer::{BoardRenderer, SvgOptions};
+use crate::game::chess_board::{ChessBoard, Piece, Player};
+use crate::game::clock::{Clocks, TimeControl};
+use crate::game::diagram::{self, AnimationOptions};
+use crate::game::history::{History, Ply};
+use crate::game::layout::Layout;
+use crate::game::move_entry::MoveEntry;
+use crate::game::network::{NetworkEvent, Outcome, Session};
+use crate::game::notation;
+use crate::game::panel_renderer::PanelRenderer;
+#[cfg(not(target_os = "android"))]
+use crate::game::config::Config;
+use crate::game::piece_registry::PieceRegistry;
+use crate::game::sprites::PieceSprites;
+use crate::game::pgn;
+use crate::game::save::SavedGame;
+use crate::game::position::Move;
+use crate::game::selection::Selection;
+use crate::game::theme::Theme;
+#[cfg(not(target_os = "android"))]
+use std::path::Path;
+
+/// A key whose action can't be taken back, it has to be pressed twice in a row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Confirmation {
+    NewGame,
+    Resign,
+}
+
+impl Confirmation {
+    fn prompt(self) -> &'static str {
+        match self {
+            Confirmation::NewGame => "Press n again to start a new game",
+            Confirmation::Resign => "Press R again to resign",
+        }
+    }
+}
+
+pub struct ChessGame {
+    registry: PieceRegistry,
+    sprites: PieceSprites,
+    history: History,
+    move_list_scroll: usize, // first visible row of the move list
+    themes: Vec<Theme>,
+    theme_index: usize,
+    renderer: BoardRenderer,
+    panel_renderer: PanelRenderer,
+    selection: Option<Selection>,
+    scene: Scene,
+    panel_scene: Scene,
+    layout: Layout,
+    auto_flip: bool, // turn the board toward the side to move after each move, for pass-and-play
+    annotation_start: Option<(usize, usize)>, // where the right button was pressed
+    move_entry: Option<MoveEntry>,
+    time_controls: Vec<TimeControl>,
+    clocks: Option<Clocks>, //none for untimed games
+    save_path: Option<PathBuf>,
+    network: Option<Session>, //the opponent plays on another instance
+    confirming: Option<Confirmation>,
+}
+
+impl Default for ChessGame {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChessGame {
+    pub fn new() -> Self {
+        #[cfg(not(target_os = "android"))]
+        let (registry, sprites, themes, time_controls) = {
+            let path = Path::new("config");
+            let config = Config::load(path).expect("couldn't read the config");
+            (PieceRegistry::from_config(&config.pieces), PieceSprites::load_from_config(&config, path).expect("couldn't read the sprites"), config.themes, config.time_controls)
+        };
+        #[cfg(target_os = "android")]
+        let (registry, sprites, themes, time_controls) = (PieceRegistry::standard(), PieceSprites::bundled(), Theme::defaults(), TimeControl::defaults());
+
+        let theme = themes.first().cloned().expect("at least one theme is required");
+        Self {
+            registry,
+            sprites,
+            history: History::new(ChessBoard::new()),
+            move_list_scroll: 0,
+            themes,
+            theme_index: 0,
+            renderer: BoardRenderer::new(theme),
+            panel_renderer: PanelRenderer::new(),
+            selection: None,
+            scene: Scene::new(),
+            panel_scene: Scene::new(),
+            layout: Layout::default(),
+            auto_flip: false,
+            annotation_start: None,
+            move_entry: None,
+            time_controls,
+            clocks: None,
+            save_path: None,
+            network: None,
+            confirming: None,
+        }
+    }
+
+    /// keep the game in `path`: it is restored from there now if it exists, and written back when the app is suspended.
+    /// Network games belong to the host, they are neither saved nor resumed
+    pub fn resume_from(&mut self, path: PathBuf) {
+        if self.network.is_some() {
+            return;
+        }
+        match SavedGame::load(&path) {
+            Ok(saved) => self.restore(saved),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+            Err(error) => log::warn!("couldn't resume the saved game: {error}"),
+        }
+        self.save_path = Some(path);
+    }
+
+    fn restore(&mut self, saved: SavedGame) {
+        if saved.version != SavedGame::VERSION || saved.variant != self.registry.piece_names() {
+            log::warn!("the saved game was played with another version or config, starting a new one");
+            return;
+        }
+        self.history = saved.history;
+        self.renderer.set_orientation(saved.orientation);
+        self.auto_flip = saved.auto_flip;
+        if let Some(index) = self.themes.iter().position(|theme| theme.name == saved.theme) {
+            self.set_theme(index);
+        }
+        self.clocks = saved.clocks;
+        self.cursor_moved();
+    }
+
+    pub fn save(&self) {
+        let Some(path) = self.save_path.as_ref().filter(|_| self.network.is_none()) else {
+            return;
+        };
+        let saved = SavedGame {
+            version: SavedGame::VERSION,
+            variant: self.registry.piece_names(),
+            history: self.history.clone(),
+            orientation: self.renderer.orientation(),
+            auto_flip: self.auto_flip,
+            theme: self.themes[self.theme_index].name.clone(),
+            clocks: self.clocks.clone(),
+        };
+        match saved.store(path) {
+            Ok(()) => log::info!("game saved to {}", path.display()),
+            Err(error) => log::error!("couldn't save the game: {error}"),
+        }
+    }
+
+    /// start again from the initial position, with fresh clocks of the same time control
+    pub fn new_game(&mut self) {
+        if self.network.is_some() {
+            log::info!("a network game can't be restarted");
+            return;
+        }
+        self.history = History::new(ChessBoard::new());
+        self.clocks = self.clocks.as_ref().map(|clocks| Clocks::new(clocks.control().clone()));
+        self.move_entry = None;
+        self.cursor_moved();
+    }
+
+    pub fn pgn(&self) -> String {
+        pgn::export(&self.history, &self.registry, self.result())
+    }
+
+    /// the PGN result of the game, from a loss on time or the end of a network game
+    pub fn result(&self) -> &'static str {
+        if let Some(outcome) = self.network.as_ref().and_then(Session::outcome) {
+            return outcome.result();
+        }
+        match self.clocks.as_ref().and_then(Clocks::flagged) {
+            Some(Player::White) => "0-1",
+            Some(Player::Black) => "1-0",
+            None => "*",
+        }
+    }
+
+    pub fn time_controls(&self) -> &[TimeControl] {
+        &self.time_controls
+    }
+
+    /// play with the given time control, or without clocks, the clocks start again from the next move
+    pub fn set_time_control(&mut self, index: Option<usize>) {
+        self.clocks = index
+            .and_then(|index| self.time_controls.get(index))
+            .map(|control| Clocks::new(control.clone()));
+    }
+
+    /// cycle through the time controls of the config, then back to an untimed game
+    fn next_time_control(&mut self) {
+        if self.network.is_some() {
+            log::info!("network games are played without clocks");
+            return;
+        }
+        let index = match &self.clocks {
+            None => 0,
+            Some(clocks) => {
+                let current = self.time_controls.iter().position(|control| control.name == clocks.control().name);
+                current.map_or(0, |index| index + 1)
+            }
+        };
+        self.set_time_control((index < self.time_controls.len()).then_some(index));
+    }
+
+    /// the side to move at the end of the main line, whose clock is running whatever is displayed
+    fn game_turn(&self) -> Player {
+        self.history.main_line_end().board.turn()
+    }
+
+    fn export_pgn(&self) {
+        #[cfg(not(target_os = "android"))]
+        match std::fs::write("game.pgn", self.pgn()) {
+            Ok(()) => log::info!("game exported to game.pgn"),
+            Err(error) => log::error!("couldn't export the game: {error}"),
+        }
+        #[cfg(target_os = "android")]
+        log::info!("{}", self.pgn());
+    }
+
+    /// the displayed position with its arrows and circles, as a standalone svg document
+    pub fn svg(&self, options: SvgOptions) -> String {
+        let ply = self.history.current();
+        self.renderer.svg_document(&ply.board, &self.registry, &self.sprites, &ply.annotations, options)
+    }
+
+    fn export_svg(&self) {
+        #[cfg(not(target_os = "android"))]
+        match std::fs::write("position.svg", self.svg(SvgOptions::default())) {
+            Ok(()) => log::info!("position exported to position.svg"),
+            Err(error) => log::error!("couldn't export the position: {error}"),
+        }
+        #[cfg(target_os = "android")]
+        log::info!("{}", self.svg(SvgOptions::default()));
+    }
+
+    /// every position of the displayed line, as seen on the board
+    pub fn animation(&self, options: &AnimationOptions) -> Animation {
+        diagram::game_animation(&self.renderer, &self.registry, &self.sprites, &self.history.line(), options)
+    }
+
+    #[cfg(not(target_os = "android"))]
+    fn export_gif(&self) {
+        let gif = HeadlessRenderer::new().and_then(|mut renderer| renderer.render_gif(&self.animation(&AnimationOptions::default())));
+        match gif.map(|gif| std::fs::write("game.gif", gif)) {
+            Ok(Ok(())) => log::info!("game exported to game.gif"),
+            Ok(Err(error)) => log::error!("couldn't write the animation: {error}"),
+            Err(error) => log::error!("couldn't render the animation: {error}"),
+        }
+    }
+
+    pub fn flip_board(&mut self) {
+        let orientation = self.renderer.orientation().opponent();
+        self.renderer.set_orientation(orientation);
+    }
+
+    pub fn set_auto_flip(&mut self, auto_flip: bool) {
+        self.auto_flip = auto_flip;
+        self.follow_turn();
+    }
+
+    pub fn themes(&self) -> &[Theme] {
+        &self.themes
+    }
+
+    /// switch the board colors, takes effect on the next frame
+    pub fn set_theme(&mut self, index: usize) {
+        if let Some(theme) = self.themes.get(index) {
+            self.theme_index = index;
+            self.renderer.set_theme(theme.clone());
+        }
+    }
+
+    fn follow_turn(&mut self) {
+        if self.auto_flip {
+            let turn = self.actual_board().turn();
+            self.renderer.set_orientation(turn);
+        }
+    }
+
+    fn clicked_on_cell(&mut self, x: usize, y: usize) {
+        match &self.selection {
+            None => {
+                // during a network game the pieces can't be picked up while the opponent is thinking
+                let waiting = self.history.is_at_main_line_end() && self.network.as_ref().is_some_and(|session| !session.can_move());
+                if !waiting {
+                    self.selection = self.actual_board().legal_choice(&self.registry, x, y);
+                }
+            }
+            Some(selection) => {
+                let played = selection.choice.kind(x, y)
+                    .and_then(|kind| self.actual_board().get_move((selection.x, selection.y), (x, y), kind));
+                if let Some(played) = played {
+                    self.play(played);
+                }
+                self.selection = None;
+            }
+        }
+    }
+
+    /// moves played from an earlier position or in a variation are analysis, they don't touch the clocks.
+    /// In a network game only the moves of the side played here are allowed, and only at the end of the game
+    fn play(&mut self, played: Move) {
+        let in_game = self.history.is_at_main_line_end();
+        if in_game && self.clocks.as_ref().is_some_and(|clocks| clocks.flagged().is_some()) {
+            return;
+        }
+        if let Some(session) = &mut self.network {
+            if !in_game {
+                log::info!("go to the last move to play");
+                return;
+            }
+            if let Err(error) = session.play(&self.registry, played) {
+                log::info!("{error}");
+                return;
+            }
+        }
+        self.record(played, in_game);
+    }
+
+    /// add the move after the displayed position
+    fn record(&mut self, played: Move, in_game: bool) {
+        let board = self.actual_board();
+        let player = board.turn();
+        let san = notation::san(board, &self.registry, played);
+        let mut new_board = *board;
+        new_board.move_piece(played);
+        let mut ply = Ply::new(new_board, Some(san), played.captured);
+        if let Some(clocks) = self.clocks.as_mut().filter(|_| in_game) {
+            clocks.moved(player);
+            ply.clock = Some(clocks.remaining(player));
+        }
+        self.history.play(ply);
+        self.cursor_moved();
+    }
+
+    /// play against another instance on the local network, waiting for it on `port`.
+    /// The game starts again from the initial position, seen from the side of `player`
+    pub fn host_game(&mut self, port: u16, player: Player) -> std::io::Result<SocketAddr> {
+        let session = Session::host(("0.0.0.0", port), player, self.registry.piece_names())?;
+        let address = session.local_addr().expect("the host listens");
+        self.start_network_game(session);
+        log::info!("waiting for the opponent on port {}", address.port());
+        Ok(address)
+    }
+
+    /// play against the instance hosting a game at `address`, the game is replaced by the one of the host once connected
+    pub fn join_game(&mut self, address: SocketAddr) {
+        self.start_network_game(Session::join(address, self.registry.piece_names()));
+    }
+
+    fn start_network_game(&mut self, session: Session) {
+        self.network = Some(session);
+        self.clocks = None;
+        self.auto_flip = false;
+        self.load_network_game();
+    }
+
+    /// go back to playing both sides here
+    pub fn leave_network_game(&mut self) {
+        self.network = None;
+    }
+
+    /// replace the history with the game of the session
+    fn load_network_game(&mut self) {
+        let Some(session) = &self.network else {
+            return;
+        };
+        let moves = session.moves().to_vec();
+        if let Some(player) = session.player() {
+            self.renderer.set_orientation(player);
+        }
+        self.history = History::new(ChessBoard::new());
+        for played in moves {
+            self.record(played, true);
+        }
+        self.cursor_moved();
+    }
+
+    fn poll_network(&mut self) {
+        let Some(events) = self.network.as_mut().map(|session| session.poll(&self.registry)) else {
+            return;
+        };
+        for event in events {
+            match event {
+                NetworkEvent::Connected => log::info!("the opponent joined"),
+                NetworkEvent::Synced => self.load_network_game(),
+                NetworkEvent::Moved(played) => {
+                    // the game goes on at the end of the main line, whatever was being looked at
+                    self.history.go_to_path(&[]);
+                    self.history.go_to(self.history.last());
+                    self.record(played, true);
+                }
+                NetworkEvent::GameOver(outcome) => log::info!("game over: {}", outcome.result()),
+                NetworkEvent::DrawOffered => log::info!("the opponent offers a draw"),
+                NetworkEvent::DrawDeclined => log::info!("the opponent declined the draw"),
+                NetworkEvent::Disconnected => log::info!("the opponent left, waiting for them to come back"),
+                NetworkEvent::Refused(reason) => log::warn!("{reason}"),
+            }
+        }
+    }
+
+    /// run the action if its key was just pressed already, `pending` is what waited for a confirmation
+    fn confirm(&mut self, pending: Option<Confirmation>, action: Confirmation) {
+        if pending != Some(action) {
+            self.confirming = Some(action);
+            return;
+        }
+        match action {
+            Confirmation::NewGame => self.new_game(),
+            Confirmation::Resign => self.resign(),
+        }
+    }
+
+    /// what the network game waits for, shown under the move list
+    fn network_status(&self) -> Option<String> {
+        let session = self.network.as_ref()?;
+        let opponent = session.player().map(Player::opponent);
+        let status = match session.outcome() {
+            Some(Outcome::Resigned(player)) => format!("{player:?} resigned"),
+            Some(Outcome::DrawAgreed) => "Draw agreed".to_string(),
+            None if !session.is_connected() => "Waiting for the opponent".to_string(),
+            None if session.draw_offer().is_some() && session.draw_offer() == opponent => "Draw offered: d accepts, Esc declines".to_string(),
+            None if session.draw_offer().is_some() => "Draw offered to the opponent".to_string(),
+            None => return None,
+        };
+        Some(status)
+    }
+
+    /// offer a draw, or accept the one offered by the opponent
+    fn draw_offer_key(&mut self) {
+        let Some(session) = &mut self.network else {
+            return;
+        };
+        let offered = session.draw_offer().is_some() && session.draw_offer() != session.player();
+        let answered = if offered { session.answer_draw(true) } else { session.offer_draw() };
+        if let Err(error) = answered {
+            log::info!("{error}");
+        }
+    }
+
+    fn resign(&mut self) {
+        if let Some(Err(error)) = self.network.as_mut().map(Session::resign) {
+            log::info!("{error}");
+        }
+    }
+
+    /// start typing moves, the square cursor starts on the selected piece or the king of the side to move
+    pub fn open_move_entry(&mut self) {
+        let board = self.actual_board();
+        let cursor = self.selection.as_ref()
+            .map(|selection| (selection.x, selection.y))
+            .or_else(|| board.royal_square(&self.registry, board.turn()))
+            .unwrap_or((0, 0));
+        self.move_entry = Some(MoveEntry::new(cursor));
+    }
+
+    /// play the typed move, or tell why it can't be played
+    fn submit_move_entry(&mut self) {
+        let Some(entry) = &mut self.move_entry else {
+            return;
+        };
+        if entry.text.is_empty() {
+            self.move_entry = None;
+            return;
+        }
+        match notation::parse_move(&self.history.current().board, &self.registry, &entry.text) {
+            Ok(played) => {
+                entry.text.clear();
+                entry.cursor = played.to;
+                self.play(played);
+            }
+            Err(error) => entry.error = Some(format!("{error}: {}", entry.text)),
+        }
+    }
+
+    /// the arrow keys follow the screen, so they are turned around with the board
+    fn move_entry_cursor(&mut self, dx: isize, dy: isize) {
+        let sign = match self.renderer.orientation() {
+            Player::White => 1,
+            Player::Black => -1,
+        };
+        if let Some(entry) = &mut self.move_entry {
+            entry.move_cursor(dx * sign, dy * sign);
+        }
+    }
+
+    /// while typing a move, the arrow keys move the square cursor and space acts as a click on it
+    fn on_move_entry_key(&mut self, key: &Key) {
+        match key {
+            Key::Named(NamedKey::Enter) => self.submit_move_entry(),
+            Key::Named(NamedKey::Backspace) => {
+                if let Some(entry) = &mut self.move_entry {
+                    entry.pop();
+                }
+            }
+            Key::Named(NamedKey::ArrowLeft) => self.move_entry_cursor(-1, 0),
+            Key::Named(NamedKey::ArrowRight) => self.move_entry_cursor(1, 0),
+            Key::Named(NamedKey::ArrowUp) => self.move_entry_cursor(0, -1),
+            Key::Named(NamedKey::ArrowDown) => self.move_entry_cursor(0, 1),
+            Key::Named(NamedKey::Space) => {
+                if let Some((x, y)) = self.move_entry.as_ref().map(|entry| entry.cursor) {
+                    self.clicked_on_cell(x, y);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn actual_ply(&self) -> &Ply {
+        self.history.current()
+    }
+
+    /// show the position after the given ply, 0 being the starting position
+    pub fn go_to(&mut self, ply: usize) {
+        self.history.go_to(ply);
+        self.cursor_moved();
+    }
+
+    pub fn step_back(&mut self) {
+        self.history.back();
+        self.cursor_moved();
+    }
+
+    pub fn step_forward(&mut self) {
+        self.history.forward();
+        self.cursor_moved();
+    }
+
+    pub fn go_to_path(&mut self, path: &[usize]) {
+        self.history.go_to_path(path);
+        self.cursor_moved();
+    }
+
+    /// show a sibling variation of the displayed move, `offset` is usually 1 or -1
+    pub fn switch_variation(&mut self, offset: isize) {
+        self.history.switch_variation(offset);
+        self.cursor_moved();
+    }
+
+    /// make the variation leading to the displayed position the main line
+    pub fn promote_variation(&mut self) {
+        if self.network.is_some() {
+            return;
+        }
+        self.history.promote();
+    }
+
+    /// delete the displayed move along with the moves played after it
+    pub fn delete_move(&mut self) {
+        if self.network.is_some() {
+            log::info!("moves of a network game can't be deleted");
+            return;
+        }
+        self.history.delete();
+        self.cursor_moved();
+    }
+
+    fn cursor_moved(&mut self) {
+        self.selection = None;
+        self.annotation_start = None;
+        self.follow_turn();
+        self.scroll_to_cursor();
+    }
+
+    fn scroll_to_cursor(&mut self) {
+        let rows = PanelRenderer::move_list_rows(&self.history);
+        let row = PanelRenderer::cursor_row(&rows, self.history.cursor());
+        let visible_rows = PanelRenderer::visible_rows(self.layout.panel_size).max(1);
+        if row < self.move_list_scroll {
+            self.move_list_scroll = row;
+        } else if row >= self.move_list_scroll + visible_rows {
+            self.move_list_scroll = row + 1 - visible_rows;
+        }
+    }
+
+    fn scroll_move_list(&mut self, rows: isize) {
+        let row_count = PanelRenderer::move_list_rows(&self.history).len();
+        let max_scroll = row_count.saturating_sub(PanelRenderer::visible_rows(self.layout.panel_size));
+        self.move_list_scroll = self.move_list_scroll.saturating_add_signed(rows).min(max_scroll);
+    }
+
+    fn actual_board(&self) -> &ChessBoard {
+        &self.actual_ply().board
+    }
+
+    /// the opponent pieces taken by `player` so far, most valuable first
+    pub fn captured_by(&self, player: Player) -> Vec<Piece> {
+        let mut captured: Vec<_> = self.history.line().into_iter()
+            .take(self.history.cursor() + 1)
+            .filter_map(|ply| ply.captured)
+            .filter(|piece| piece.player != player)
+            .collect();
+        captured.sort_by_key(|piece| std::cmp::Reverse(self.registry.get_value(piece.piece_kind)));
+        captured
+    }
+
+    /// how much more material `player` has on the board than the opponent
+    pub fn material_advantage(&self, player: Player) -> i32 {
+        let board = self.actual_board();
+        board.material(&self.registry, player) as i32 - board.material(&self.registry, player.opponent()) as i32
+    }
+
+    fn cell_under(&self, x: f64, y: f64) -> Option<(usize, usize)> {
+        let point_on_board = self.layout.board.inverse() * Point::new(x, y);
+        self.renderer.cell_at(point_on_board)
+    }
+
+    /// plain right drag is green, shift makes it red, alt or control blue, and both yellow
+    fn brush(modifiers: ModifiersState) -> Brush {
+        let alternate = modifiers.alt_key() || modifiers.control_key();
+        match (modifiers.shift_key(), alternate) {
+            (false, false) => Brush::Green,
+            (true, false) => Brush::Red,
+            (false, true) => Brush::Blue,
+            (true, true) => Brush::Yellow,
+        }
+    }
+
+    fn refresh(&mut self) {
+        let ply = self.history.current();
+        self.scene.reset();
+        self.renderer.draw_board(&mut self.scene);
+        self.renderer.draw_highlights(&ply.board, &self.registry, &mut self.scene);
+        self.renderer.draw_pieces(&ply.board, &self.sprites, &mut self.scene);
+        self.renderer.draw_selection(&self.selection, &mut self.scene);
+        self.renderer.draw_annotations(&ply.annotations, &mut self.scene);
+        if let Some(entry) = &self.move_entry {
+            self.renderer.draw_cursor(entry.cursor.0, entry.cursor.1, &mut self.scene);
+        }
+
+        let white = self.captured_by(Player::White);
+        let black = self.captured_by(Player::Black);
+        let captured = [
+            (Player::White, &white[..], self.material_advantage(Player::White)),
+            (Player::Black, &black[..], self.material_advantage(Player::Black)),
+        ];
+        let top = self.renderer.orientation().opponent();
+        self.panel_scene.reset();
+        self.panel_renderer.draw_captured(captured, top, self.layout.panel_size, &self.sprites, &mut self.panel_scene);
+        if let Some(clocks) = &self.clocks {
+            self.panel_renderer.draw_clocks(clocks, self.game_turn(), top, self.layout.panel_size, &mut self.panel_scene);
+        }
+        let rows = PanelRenderer::move_list_rows(&self.history);
+        self.panel_renderer.draw_move_list(&rows, &self.history, self.move_list_scroll, self.layout.panel_size, &mut self.panel_scene);
+        if let Some(entry) = &self.move_entry {
+            self.panel_renderer.draw_move_entry(entry, self.layout.panel_size, &mut self.panel_scene);
+        } else if let Some(confirmation) = self.confirming {
+            self.panel_renderer.draw_status(confirmation.prompt(), self.layout.panel_size, &mut self.panel_scene);
+        } else if let Some(status) = self.network_status() {
+            self.panel_renderer.draw_status(&status, self.layout.panel_size, &mut self.panel_scene);
+        }
+    }
+}
+
+impl LogicHandler for ChessGame {
+    fn on_mouse_click(&mut self, x: f64, y: f64) {
+        if let Some((x, y)) = self.cell_under(x, y) {
+            self.clicked_on_cell(x, y);
+            return;
+        }
+        self.selection = None;
+
+        let point_on_panel = self.layout.panel.inverse() * Point::new(x, y);
+        let rows = PanelRenderer::move_list_rows(&self.history);
+        if let Some(path) = PanelRenderer::target_at(point_on_panel, self.layout.panel_size, self.move_list_scroll, &rows, &self.history) {
+            self.go_to_path(&path);
+        }
+    }
+
+    fn on_scroll(&mut self, _x: f64, _y: f64, rows: f64) {
+        self.scroll_move_list(-rows.round() as isize);
+    }
+
+    fn on_right_press(&mut self, x: f64, y: f64) {
+        self.annotation_start = self.cell_under(x, y);
+    }
+
+    fn on_right_release(&mut self, x: f64, y: f64, modifiers: ModifiersState) {
+        let (Some(from), Some(to)) = (self.annotation_start.take(), self.cell_under(x, y)) else {
+            return;
+        };
+        let brush = Self::brush(modifiers);
+        let annotations = &mut self.history.current_mut().annotations;
+        if from == to {
+            annotations.toggle_circle(Circle { brush, square: to });
+        } else {
+            annotations.toggle_arrow(Arrow { brush, from, to });
+        }
+    }
+
+    fn on_exit_press(&mut self) {
+        if self.confirming.take().is_some() {
+            return;
+        }
+        if self.move_entry.is_some() {
+            self.move_entry = None;
+            self.selection = None;
+        } else if self.selection.is_some() {
+            self.selection = None;
+        } else if let Some(session) = self.network.as_mut().filter(|session| session.draw_offer().is_some_and(|player| Some(player) != session.player())) {
+            let _ = session.answer_draw(false);
+        } else {
+            self.step_back();
+        }
+    }
+
+    fn on_key_press(&mut self, key: &Key) {
+        if self.move_entry.is_some() {
+            self.on_move_entry_key(key);
+            return;
+        }
+        // any other key cancels a confirmation, except the ones held to type it and the exit keys which cancel it on release
+        let kept = matches!(key, Key::Named(NamedKey::Shift | NamedKey::CapsLock | NamedKey::Escape | NamedKey::AudioVolumeUp | NamedKey::BrowserBack));
+        let confirming = if kept { self.confirming } else { self.confirming.take() };
+        match key {
+            Key::Named(NamedKey::Enter) => self.open_move_entry(),
+            Key::Named(NamedKey::ArrowLeft) => self.step_back(),
+            Key::Named(NamedKey::ArrowRight | NamedKey::BrowserForward | NamedKey::AudioVolumeDown) => self.step_forward(),
+            Key::Named(NamedKey::Home) => self.go_to(0),
+            Key::Named(NamedKey::End) => self.go_to(self.history.last()),
+            Key::Named(NamedKey::ArrowUp) => self.switch_variation(-1),
+            Key::Named(NamedKey::ArrowDown) => self.switch_variation(1),
+            Key::Named(NamedKey::Delete) => self.delete_move(),
+            Key::Character(character) => match character.as_str() {
+                "f" => self.flip_board(),
+                "a" => self.set_auto_flip(!self.auto_flip),
+                "t" => self.set_theme((self.theme_index + 1) % self.themes.len()),
+                "p" => self.export_pgn(),
+                "s" => self.export_svg(),
+                #[cfg(not(target_os = "android"))]
+                "g" => self.export_gif(),
+                "m" => self.promote_variation(),
+                "c" => self.next_time_control(),
+                "n" => self.confirm(confirming, Confirmation::NewGame),
+                "d" => self.draw_offer_key(),
+                "R" => self.confirm(confirming, Confirmation::Resign),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    fn on_suspend(&mut self) {
+        self.save();
+    }
+
+    fn on_text_input(&mut self, text: &str) {
+        if let Some(entry) = &mut self.move_entry {
+            entry.push(text);
+        }
+    }
+
+    fn draw(&mut self, scene: &mut Scene, duration: Duration) {
+        self.poll_network();
+        let turn = self.game_turn();
+        if let Some(clocks) = &mut self.clocks {
+            clocks.tick(turn, duration);
+        }
+        self.refresh();
+        scene.append(&self.scene, Some(self.layout.board));
+        scene.append(&self.panel_scene, Some(self.layout.panel));
+    }
+
+    fn surface_resize(&mut self, width: u32, height: u32) {
+        self.layout = Layout::new(width, height);
+        self.scroll_to_cursor();
+    }
+}
\ No newline at end of file
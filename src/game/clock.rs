@@ -0,0 +1,195 @@
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::game::chess_board::Player;
+
+/// A part of the game with its own time budget, added to what is left from the previous period.
+/// In the config every duration is written in seconds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Period {
+    #[serde(default)]
+    pub moves: Option<u32>, //moves to play before the next period starts, none for the rest of the game
+    #[serde(with = "seconds")]
+    pub time: Duration,
+    #[serde(default, with = "seconds")]
+    pub increment: Duration, //Fischer increment, added after every move
+    #[serde(default, with = "seconds")]
+    pub delay: Duration, //Bronstein delay, the time spent on a move is given back up to this much
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeControl {
+    pub name: String,
+    pub periods: Vec<Period>,
+}
+
+impl TimeControl {
+    /// used when the config doesn't provide any time control
+    pub fn defaults() -> Vec<Self> {
+        let minutes = |minutes: u64| Duration::from_secs(minutes * 60);
+        let period = |moves, time, increment, delay| Period { moves, time, increment, delay };
+        vec![
+            Self {
+                name: "Blitz 5+3".to_string(),
+                periods: vec![period(None, minutes(5), Duration::from_secs(3), Duration::ZERO)],
+            },
+            Self {
+                name: "Rapid 15 delay 10".to_string(),
+                periods: vec![period(None, minutes(15), Duration::ZERO, Duration::from_secs(10))],
+            },
+            Self {
+                name: "Classical 40/90, 30+30".to_string(),
+                periods: vec![
+                    period(Some(40), minutes(90), Duration::from_secs(30), Duration::ZERO),
+                    period(None, minutes(30), Duration::from_secs(30), Duration::ZERO),
+                ],
+            },
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PlayerClock {
+    remaining: Duration,
+    period: usize,
+    moves: u32, //moves played in the current period
+}
+
+/// The time left to each player. Nothing runs until the first move is played,
+/// then only the clock of the side to move counts down.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Clocks {
+    control: TimeControl,
+    clocks: [PlayerClock; 2],
+    thinking: Duration, //spent on the move being thought about
+    started: bool,
+    flagged: Option<Player>,
+}
+
+impl Clocks {
+    pub fn new(control: TimeControl) -> Self {
+        let time = control.periods.first().map(|period| period.time).unwrap_or_default();
+        let clock = PlayerClock { remaining: time, period: 0, moves: 0 };
+        Self {
+            control,
+            clocks: [clock.clone(), clock],
+            thinking: Duration::ZERO,
+            started: false,
+            flagged: None,
+        }
+    }
+
+    fn index(player: Player) -> usize {
+        match player {
+            Player::White => 0,
+            Player::Black => 1,
+        }
+    }
+
+    #[cfg(feature = "graphics")]
+    pub fn control(&self) -> &TimeControl {
+        &self.control
+    }
+
+    pub fn remaining(&self, player: Player) -> Duration {
+        self.clocks[Self::index(player)].remaining
+    }
+
+    /// the player who ran out of time, and lost
+    pub fn flagged(&self) -> Option<Player> {
+        self.flagged
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.started && self.flagged.is_none()
+    }
+
+    pub fn tick(&mut self, turn: Player, elapsed: Duration) {
+        if !self.is_running() {
+            return;
+        }
+        self.thinking += elapsed;
+        let clock = &mut self.clocks[Self::index(turn)];
+        clock.remaining = clock.remaining.saturating_sub(elapsed);
+        if clock.remaining.is_zero() {
+            self.flagged = Some(turn);
+        }
+    }
+
+    /// `player` just played: give them their bonus and start their next period when it is due
+    pub fn moved(&mut self, player: Player) {
+        if self.flagged.is_some() {
+            return;
+        }
+        self.started = true;
+        let thinking = std::mem::take(&mut self.thinking);
+        let periods = &self.control.periods;
+        let clock = &mut self.clocks[Self::index(player)];
+        let Some(period) = periods.get(clock.period) else {
+            return;
+        };
+
+        // nothing is thought before the clocks start, so the first move only gets the increment
+        clock.remaining += period.increment + thinking.min(period.delay);
+        clock.moves += 1;
+        if period.moves.is_some_and(|moves| clock.moves >= moves) {
+            if let Some(next) = periods.get(clock.period + 1) {
+                clock.period += 1;
+                clock.moves = 0;
+                clock.remaining += next.time;
+            }
+        }
+    }
+}
+
+/// `m:ss`, with the hours when there are some, and tenths of seconds when time is running out
+#[cfg(feature = "graphics")]
+pub fn format(time: Duration) -> String {
+    let seconds = time.as_secs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else if time < Duration::from_secs(10) {
+        format!("{minutes}:{seconds:02}.{}", time.subsec_millis() / 100)
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
+
+/// `h:mm:ss` as expected by the PGN `%clk` command
+#[cfg(feature = "graphics")]
+pub fn pgn_format(time: Duration) -> String {
+    let seconds = time.as_secs();
+    format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+mod seconds {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(duration.as_secs_f64())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        let seconds = f64::deserialize(deserializer)?;
+        Duration::try_from_secs_f64(seconds).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_move_gets_the_increment() {
+        let second = Duration::from_secs(1);
+        let mut clocks = Clocks::new(TimeControl {
+            name: "Blitz".to_string(),
+            periods: vec![Period { moves: None, time: 3 * second, increment: 2 * second, delay: Duration::ZERO }],
+        });
+        clocks.moved(Player::White);
+        clocks.tick(Player::Black, second);
+        clocks.moved(Player::Black);
+        assert_eq!([clocks.remaining(Player::White), clocks.remaining(Player::Black)], [5 * second, 4 * second]);
+    }
+}
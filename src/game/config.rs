@@ -0,0 +1,28 @@
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::game::clock::TimeControl;
+use crate::game::piece_registry::PieceConfig;
+#[cfg(feature = "graphics")]
+use crate::game::theme::Theme;
+
+#[derive(Serialize, Deserialize)]
+pub struct Config {
+    pub pieces: Vec<PieceConfig>,
+    #[cfg(feature = "graphics")]
+    #[serde(default = "Theme::defaults", deserialize_with = "Theme::deserialize_list")]
+    pub themes: Vec<Theme>,
+    #[serde(default = "TimeControl::defaults")]
+    pub time_controls: Vec<TimeControl>,
+}
+
+impl Config {
+    /// read `chess_config.json` in `path`, the error tells which file couldn't be read or parsed
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let config_path = path.join("chess_config.json");
+        let in_file = |error: io::Error| io::Error::new(error.kind(), format!("{}: {error}", config_path.display()));
+        let file = File::open(&config_path).map_err(in_file)?;
+        serde_json::from_reader(file).map_err(|error| in_file(error.into()))
+    }
+}
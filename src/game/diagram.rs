@@ -0,0 +1,151 @@
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+use vello::kurbo::Affine;
+use vello::peniko::Color;
+use vello::Scene;
+use crate::game::annotation::Annotations;
+use crate::game::board_renderer::{BoardRenderer, SvgOptions};
+use crate::game::chess_board::{ChessBoard, Player};
+use crate::game::config::Config;
+use crate::game::fen::{self, FenError};
+use crate::game::history::Ply;
+use crate::game::pgn::{self, PgnError};
+use crate::game::piece_registry::PieceRegistry;
+use crate::game::sprites::PieceSprites;
+use crate::game::text::{Align, TextRenderer};
+use crate::headless::Animation;
+
+/// How a game is turned into an animation.
+#[derive(Debug, Clone)]
+pub struct AnimationOptions {
+    pub size: u32, //side of the board in pixels, the caption is added below
+    pub delay: Duration, //how long each position is shown
+    pub final_delay: Duration, //the final position stays longer before the animation loops
+    pub highlights: bool, //last move and check
+    pub captions: bool, //the move that led to the position, written under the board
+}
+
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        Self {
+            size: 480,
+            delay: Duration::from_secs(1),
+            final_delay: Duration::from_secs(3),
+            highlights: true,
+            captions: true,
+        }
+    }
+}
+
+const CAPTION_HEIGHT: f64 = 80.0;
+const CAPTION_SIZE: f32 = 40.0;
+const CAPTION_MARGIN: f64 = 20.0;
+
+/// a frame for every ply of `line`, drawn like the board on screen
+pub fn game_animation(renderer: &BoardRenderer, registry: &PieceRegistry, sprites: &PieceSprites, line: &[&Ply], options: &AnimationOptions) -> Animation {
+    let scale = options.size as f64 / BoardRenderer::BOARD_SIZE;
+    let caption_height = if options.captions { CAPTION_HEIGHT } else { 0.0 };
+    let text = TextRenderer::new();
+    let baseline = BoardRenderer::BOARD_SIZE + (caption_height + text.cap_height(CAPTION_SIZE)) / 2.0;
+
+    let frames = line.iter().enumerate().map(|(index, ply)| {
+        let mut frame = Scene::new();
+        renderer.draw_board(&mut frame);
+        if options.highlights {
+            renderer.draw_highlights(&ply.board, registry, &mut frame);
+        }
+        renderer.draw_pieces(&ply.board, sprites, &mut frame);
+        renderer.draw_annotations(&ply.annotations, &mut frame);
+        if options.captions {
+            let number = index.div_ceil(2);
+            let caption = match (&ply.san, ply.board.turn().opponent()) {
+                (None, _) => "Start".to_string(),
+                (Some(san), Player::White) => format!("{number}. {san}"),
+                (Some(san), Player::Black) => format!("{number}... {san}"),
+            };
+            text.draw(&mut frame, &caption, CAPTION_SIZE, Align::Start, Color::WHITE, Affine::translate((CAPTION_MARGIN, baseline)));
+        }
+
+        let mut scene = Scene::new();
+        scene.append(&frame, Some(Affine::scale(scale)));
+        let delay = if index + 1 == line.len() { options.final_delay } else { options.delay };
+        (scene, delay)
+    }).collect();
+
+    Animation {
+        width: options.size,
+        height: ((BoardRenderer::BOARD_SIZE + caption_height) * scale).round() as u32,
+        background: Color::rgb8(40, 40, 40),
+        frames,
+    }
+}
+
+/// Draws positions outside of the app, for bug reports and documentation.
+pub struct Diagram {
+    registry: PieceRegistry,
+    sprites: PieceSprites,
+    renderer: BoardRenderer,
+    annotations: Annotations,
+}
+
+impl Diagram {
+    /// side of the drawn board in scene units, scale the scene to get another size
+    pub const SIZE: f64 = BoardRenderer::BOARD_SIZE;
+
+    /// the pieces and themes come from the config in `path`, the first theme is used unless `theme` names another
+    pub fn load(path: &Path, theme: Option<&str>) -> io::Result<Self> {
+        let config = Config::load(path)?;
+        let registry = PieceRegistry::from_config(&config.pieces);
+        let sprites = PieceSprites::load_from_config(&config, path)?;
+        let theme = config.themes.iter()
+            .find(|candidate| Some(candidate.name.as_str()) == theme)
+            .or(config.themes.first())
+            .cloned()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "at least one theme is required"))?;
+        Ok(Self {
+            registry,
+            sprites,
+            renderer: BoardRenderer::new(theme),
+            annotations: Annotations::default(),
+        })
+    }
+
+    /// the player whose pieces are at the bottom
+    pub fn set_orientation(&mut self, orientation: Player) {
+        self.renderer.set_orientation(orientation);
+    }
+
+    /// arrows and circles to draw over the pieces, as PGN `[%cal]` and `[%csl]` commands
+    pub fn set_annotations(&mut self, commands: &str) {
+        self.annotations = Annotations::from_pgn_commands(commands);
+    }
+
+    /// a standalone svg document of a FEN position
+    pub fn svg_fen(&self, fen: &str, options: SvgOptions) -> Result<String, FenError> {
+        let board = fen::parse(fen, &self.registry)?;
+        Ok(self.renderer.svg_document(&board, &self.registry, &self.sprites, &self.annotations, options))
+    }
+
+    /// the board with coordinates, check highlight, pieces and annotations of a FEN position, scaled by `scale`
+    pub fn draw_fen(&self, fen: &str, scale: f64, scene: &mut Scene) -> Result<(), FenError> {
+        let board = fen::parse(fen, &self.registry)?;
+        self.draw(&board, scale, scene);
+        Ok(())
+    }
+
+    /// every position of the main line of a PGN game
+    pub fn animate_pgn(&self, pgn: &str, options: &AnimationOptions) -> Result<Animation, PgnError> {
+        let history = pgn::import(pgn, &self.registry)?;
+        Ok(game_animation(&self.renderer, &self.registry, &self.sprites, &history.line(), options))
+    }
+
+    fn draw(&self, board: &ChessBoard, scale: f64, scene: &mut Scene) {
+        let mut board_scene = Scene::new();
+        self.renderer.draw_board(&mut board_scene);
+        self.renderer.draw_highlights(board, &self.registry, &mut board_scene);
+        self.renderer.draw_pieces(board, &self.sprites, &mut board_scene);
+        self.renderer.draw_annotations(&self.annotations, &mut board_scene);
+        scene.append(&board_scene, Some(Affine::scale(scale)));
+    }
+}
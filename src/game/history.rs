@@ -0,0 +1,234 @@
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::game::annotation::Annotations;
+use crate::game::chess_board::{ChessBoard, Piece};
+
+/// A position reached during the game, with the move that led to it.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Ply {
+    pub board: ChessBoard,
+    pub san: Option<String>, //none for the starting position
+    pub captured: Option<Piece>,
+    pub annotations: Annotations,
+    pub clock: Option<Duration>, //time left to the player who made the move, in timed games
+}
+
+impl Ply {
+    pub fn new(board: ChessBoard, san: Option<String>, captured: Option<Piece>) -> Self {
+        Self {
+            board,
+            san,
+            captured,
+            annotations: Annotations::default(),
+            clock: None,
+        }
+    }
+}
+
+/// A ply with the moves played from it, the first child is the main continuation and the others are variations.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Node {
+    pub ply: Ply,
+    pub children: Vec<Node>,
+}
+
+impl Node {
+    fn new(ply: Ply) -> Self {
+        Self {
+            ply,
+            children: Vec::new(),
+        }
+    }
+}
+
+/// The game as a tree of positions, with a cursor on the displayed one.
+/// `line` is the path of child indices to a leaf that is shown in the move list, the cursor is `depth` plies along it,
+/// so stepping back and forward stays in the same variation.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct History {
+    root: Node,
+    line: Vec<usize>,
+    depth: usize,
+    #[serde(default = "first_move")]
+    full_move: usize, //the number of the first move, games set up from a FEN record may start later
+}
+
+fn first_move() -> usize {
+    1
+}
+
+impl History {
+    pub fn new(board: ChessBoard) -> Self {
+        Self::starting_at(board, first_move())
+    }
+
+    /// a game whose first move, played from `board`, has the number `full_move`
+    pub fn starting_at(board: ChessBoard, full_move: usize) -> Self {
+        Self {
+            root: Node::new(Ply::new(board, None, None)),
+            line: Vec::new(),
+            depth: 0,
+            full_move,
+        }
+    }
+
+    /// the number of the first move
+    pub fn full_move(&self) -> usize {
+        self.full_move
+    }
+
+    pub fn root(&self) -> &Node {
+        &self.root
+    }
+
+    pub fn node(&self, path: &[usize]) -> &Node {
+        path.iter().fold(&self.root, |node, &index| &node.children[index])
+    }
+
+    // takes the root apart from self so the path can be borrowed from the line
+    fn node_mut<'a>(root: &'a mut Node, path: &[usize]) -> &'a mut Node {
+        path.iter().fold(root, |node, &index| &mut node.children[index])
+    }
+
+    /// the path from the root to the displayed ply
+    pub fn path(&self) -> &[usize] {
+        &self.line[..self.depth]
+    }
+
+    /// the plies of the displayed line, from the starting position to the end of the variation
+    pub fn line(&self) -> Vec<&Ply> {
+        let mut node = &self.root;
+        let mut plies = vec![&node.ply];
+        for &index in &self.line {
+            node = &node.children[index];
+            plies.push(&node.ply);
+        }
+        plies
+    }
+
+    /// the path to each ply of the displayed line
+    pub fn line_path(&self, ply: usize) -> &[usize] {
+        &self.line[..ply]
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.depth
+    }
+
+    pub fn last(&self) -> usize {
+        self.line.len()
+    }
+
+    pub fn current(&self) -> &Ply {
+        &self.node(self.path()).ply
+    }
+
+    /// the last ply of the main line, where the game goes on whatever line is displayed
+    pub fn main_line_end(&self) -> &Ply {
+        let mut node = &self.root;
+        while let Some(child) = node.children.first() {
+            node = child;
+        }
+        &node.ply
+    }
+
+    /// whether the displayed ply is the last one of the main line
+    pub fn is_at_main_line_end(&self) -> bool {
+        self.path().iter().all(|&index| index == 0) && self.node(self.path()).children.is_empty()
+    }
+
+    pub fn current_mut(&mut self) -> &mut Ply {
+        &mut Self::node_mut(&mut self.root, &self.line[..self.depth]).ply
+    }
+
+    /// follow the main continuation from the end of the line
+    fn extend_line(&mut self) {
+        let mut node = self.node(&self.line);
+        let mut extension = Vec::new();
+        while !node.children.is_empty() {
+            extension.push(0);
+            node = &node.children[0];
+        }
+        self.line.extend(extension);
+    }
+
+    pub fn go_to(&mut self, ply: usize) {
+        self.depth = ply.min(self.last());
+    }
+
+    pub fn go_to_path(&mut self, path: &[usize]) {
+        self.line = path.to_vec();
+        self.depth = path.len();
+        self.extend_line();
+    }
+
+    pub fn back(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+    }
+
+    pub fn forward(&mut self) {
+        self.go_to(self.depth + 1);
+    }
+
+    /// the number of moves that could have been played instead of the given ply of the line, itself included
+    pub fn alternatives(&self, ply: usize) -> usize {
+        if ply == 0 {
+            return 1;
+        }
+        self.node(&self.line[..ply - 1]).children.len()
+    }
+
+    /// show the next or previous variation branching at the displayed ply
+    pub fn switch_variation(&mut self, offset: isize) {
+        if self.depth == 0 {
+            return;
+        }
+        let count = self.alternatives(self.depth) as isize;
+        let index = self.line[self.depth - 1] as isize + offset;
+        if (0..count).contains(&index) {
+            let mut path = self.path().to_vec();
+            *path.last_mut().unwrap() = index as usize;
+            self.go_to_path(&path);
+        }
+    }
+
+    /// play a move from the displayed position.
+    /// A move that was already played from there is only stepped into, a new one starts a variation
+    /// unless the position had no continuation yet, in which case it extends the line.
+    pub fn play(&mut self, ply: Ply) {
+        let mut path = self.path().to_vec();
+        let node = Self::node_mut(&mut self.root, &path);
+        let index = match node.children.iter().position(|child| child.ply.san == ply.san) {
+            Some(index) => index,
+            None => {
+                node.children.push(Node::new(ply));
+                node.children.len() - 1
+            }
+        };
+        path.push(index);
+        self.go_to_path(&path);
+    }
+
+    /// make every variation leading to the displayed ply the main continuation of its parent
+    pub fn promote(&mut self) {
+        for depth in 0..self.depth {
+            let index = self.line[depth];
+            if index != 0 {
+                let parent = Self::node_mut(&mut self.root, &self.line[..depth]);
+                let child = parent.children.remove(index);
+                parent.children.insert(0, child);
+                self.line[depth] = 0;
+            }
+        }
+    }
+
+    /// remove the displayed ply and everything played after it, the cursor goes back to the previous ply
+    pub fn delete(&mut self) {
+        let Some((&index, parent_path)) = self.path().split_last() else {
+            return;
+        };
+        let parent_path = parent_path.to_vec();
+        Self::node_mut(&mut self.root, &parent_path).children.remove(index);
+        self.go_to_path(&parent_path);
+    }
+}
@@ -0,0 +1,43 @@
+use vello::kurbo::{Affine, Size, Vec2};
+use crate::game::board_renderer::BoardRenderer;
+
+/// Where the board and the side panel go in the window.
+/// Both are drawn in board units, the panel sits beside the board in wide windows and below it in tall ones.
+pub struct Layout {
+    pub board: Affine,
+    pub panel: Affine,
+    pub panel_size: Size,
+}
+
+impl Layout {
+    pub const PANEL_DEPTH: f64 = 360.0; //width of the panel beside the board, or its height below it
+
+    pub fn new(width: u32, height: u32) -> Self {
+        let width = width as f64;
+        let height = height as f64;
+        let board_size = BoardRenderer::BOARD_SIZE;
+
+        let (content, panel_offset, panel_size) = if width >= height {
+            (Vec2::new(board_size + Self::PANEL_DEPTH, board_size), Vec2::new(board_size, 0.0), Size::new(Self::PANEL_DEPTH, board_size))
+        } else {
+            (Vec2::new(board_size, board_size + Self::PANEL_DEPTH), Vec2::new(0.0, board_size), Size::new(board_size, Self::PANEL_DEPTH))
+        };
+
+        let scale_factor = f64::min(width / content.x, height / content.y);
+        let margin = (Vec2::new(width, height) - content * scale_factor) / 2.0;
+        let board = Affine::scale(scale_factor).then_translate(margin);
+        Self {
+            board,
+            panel: board * Affine::translate(panel_offset),
+            panel_size,
+        }
+    }
+}
+
+impl Default for Layout {
+    /// one window pixel per board unit, until the surface size is known
+    fn default() -> Self {
+        let size = BoardRenderer::BOARD_SIZE;
+        Self::new((size + Self::PANEL_DEPTH) as u32, size as u32)
+    }
+}
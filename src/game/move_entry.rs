@@ -0,0 +1,40 @@
+use crate::game::grid::BOARD_SIZE;
+
+/// The keyboard input opened with Enter: the move being typed and a square cursor moved with the arrow keys.
+pub struct MoveEntry {
+    pub text: String,
+    pub error: Option<String>, // why the last entry was refused, cleared when typing again
+    pub cursor: (usize, usize),
+}
+
+impl MoveEntry {
+    /// only characters that can appear in a move are kept
+    const ACCEPTED: &'static str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789-=+#";
+
+    pub fn new(cursor: (usize, usize)) -> Self {
+        Self {
+            text: String::new(),
+            error: None,
+            cursor,
+        }
+    }
+
+    pub fn push(&mut self, text: &str) {
+        self.error = None;
+        self.text.extend(text.chars().filter(|c| Self::ACCEPTED.contains(*c)));
+    }
+
+    pub fn pop(&mut self) {
+        self.error = None;
+        self.text.pop();
+    }
+
+    /// move the cursor in grid coordinates, stopping at the edges
+    pub fn move_cursor(&mut self, dx: isize, dy: isize) {
+        let (x, y) = self.cursor;
+        self.cursor = (
+            x.saturating_add_signed(dx).min(BOARD_SIZE - 1),
+            y.saturating_add_signed(dy).min(BOARD_SIZE - 1),
+        );
+    }
+}
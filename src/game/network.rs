@@ -0,0 +1,673 @@
+use std::fmt::{Display, Formatter};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::game::chess_board::{ChessBoard, Player};
+use crate::game::piece_registry::PieceRegistry;
+use crate::game::position::Move;
+
+/// bumped whenever a message changes, peers with another version are refused
+pub const PROTOCOL_VERSION: u32 = 2;
+pub const DEFAULT_PORT: u16 = 7878;
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+const ACCEPT_POLL: Duration = Duration::from_millis(50);
+
+/// How a network game ended, besides what happens on the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Outcome {
+    Resigned(Player), //the player who gave up
+    DrawAgreed,
+}
+
+impl Outcome {
+    /// the PGN result
+    pub fn result(self) -> &'static str {
+        match self {
+            Outcome::Resigned(Player::White) => "0-1",
+            Outcome::Resigned(Player::Black) => "1-0",
+            Outcome::DrawAgreed => "1/2-1/2",
+        }
+    }
+}
+
+/// What the peers send each other, one json object per line.
+/// Both start with a hello, then the host sends the whole game so a guest joining again catches up.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Message {
+    Hello { version: u32, variant: Vec<String> },
+    Sync { guest: Player, moves: Vec<Move>, outcome: Option<Outcome>, draw_offer: Option<Player> },
+    Move { ply: usize, played: Move }, //ply is the number of moves played before this one
+    Resign,
+    OfferDraw,
+    AnswerDraw { accept: bool },
+}
+
+/// What happened on the other end since the last poll.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NetworkEvent {
+    Connected, //the guest is in, on the host
+    Synced, //the game was replaced by the one of the host, on the guest
+    Moved(Move),
+    GameOver(Outcome),
+    DrawOffered,
+    DrawDeclined,
+    Disconnected,
+    Refused(String), //the peer sent something that doesn't fit the game, it was disconnected
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetworkError {
+    NotConnected,
+    NotYourTurn,
+    Illegal,
+    GameOver,
+}
+
+impl Display for NetworkError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetworkError::NotConnected => write!(f, "the opponent isn't connected"),
+            NetworkError::NotYourTurn => write!(f, "it's the opponent's turn"),
+            NetworkError::Illegal => write!(f, "illegal move"),
+            NetworkError::GameOver => write!(f, "the game is over"),
+        }
+    }
+}
+
+impl std::error::Error for NetworkError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Host,
+    Guest,
+}
+
+// sent by the threads owning the sockets
+enum Link {
+    Connected(u64, TcpStream), //a clone of the stream to write to
+    Received(u64, Message),
+    Closed(u64),
+}
+
+/// A game against another instance on the network.
+/// The host owns the game: the guest reconnects on its own when the connection drops and gets the whole game again,
+/// and a move either side refuses drops the connection so the guest resyncs. Nobody else gets in while the guest is connected.
+pub struct Session {
+    role: Role,
+    player: Option<Player>, //the side played here, the guest learns it from the host
+    variant: Vec<String>,
+    board: ChessBoard, //the position after `moves`
+    moves: Vec<Move>,
+    outcome: Option<Outcome>,
+    draw_offer: Option<Player>, //the player whose offer is waiting for an answer
+    connection: Option<(u64, TcpStream)>,
+    ready: bool, //hello exchanged, and synced on the guest
+    links: Receiver<Link>,
+    stop: Arc<AtomicBool>,
+    local_addr: Option<SocketAddr>,
+}
+
+impl Session {
+    /// wait for a guest on `address`, playing `player`
+    pub fn host(address: impl ToSocketAddrs, player: Player, variant: Vec<String>) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        let local_addr = listener.local_addr()?;
+        let (sender, links) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let stopped = stop.clone();
+        thread::spawn(move || {
+            let mut id = 0;
+            while !stopped.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        id += 1;
+                        if stream.set_nonblocking(false).is_ok() && accept(id, stream, &sender).is_none() {
+                            return;
+                        }
+                    }
+                    Err(error) if error.kind() == io::ErrorKind::WouldBlock => thread::sleep(ACCEPT_POLL),
+                    Err(error) => {
+                        log::warn!("couldn't accept a connection: {error}");
+                        thread::sleep(RETRY_DELAY);
+                    }
+                }
+            }
+        });
+
+        Ok(Self::new(Role::Host, Some(player), variant, links, stop, Some(local_addr)))
+    }
+
+    /// connect to the host at `address`, again and again until the session is dropped
+    pub fn join(address: SocketAddr, variant: Vec<String>) -> Self {
+        let (sender, links) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let stopped = stop.clone();
+        thread::spawn(move || {
+            let mut id = 0;
+            while !stopped.load(Ordering::Relaxed) {
+                if let Ok(stream) = TcpStream::connect_timeout(&address, RETRY_DELAY * 4) {
+                    id += 1;
+                    let Some(reader) = connected(id, stream, &sender) else {
+                        return;
+                    };
+                    read_messages(id, reader, &sender);
+                }
+                thread::sleep(RETRY_DELAY);
+            }
+        });
+
+        Self::new(Role::Guest, None, variant, links, stop, None)
+    }
+
+    fn new(role: Role, player: Option<Player>, variant: Vec<String>, links: Receiver<Link>, stop: Arc<AtomicBool>, local_addr: Option<SocketAddr>) -> Self {
+        Self {
+            role,
+            player,
+            variant,
+            board: ChessBoard::new(),
+            moves: Vec::new(),
+            outcome: None,
+            draw_offer: None,
+            connection: None,
+            ready: false,
+            links,
+            stop,
+            local_addr,
+        }
+    }
+
+    /// the side played here, unknown to the guest until it is synced
+    pub fn player(&self) -> Option<Player> {
+        self.player
+    }
+
+    /// where the host listens, the port is picked by the system when asked for port 0
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addr
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.ready
+    }
+
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.outcome
+    }
+
+    /// the player whose draw offer is waiting for an answer
+    pub fn draw_offer(&self) -> Option<Player> {
+        self.draw_offer
+    }
+
+    /// whether the side played here can move now
+    pub fn can_move(&self) -> bool {
+        self.ready && self.outcome.is_none() && self.player == Some(self.board.turn())
+    }
+
+    fn check_playing(&self) -> Result<Player, NetworkError> {
+        match self.player {
+            _ if self.outcome.is_some() => Err(NetworkError::GameOver),
+            Some(player) if self.ready => Ok(player),
+            _ => Err(NetworkError::NotConnected),
+        }
+    }
+
+    pub fn play(&mut self, registry: &PieceRegistry, played: Move) -> Result<(), NetworkError> {
+        let player = self.check_playing()?;
+        if player != self.board.turn() {
+            return Err(NetworkError::NotYourTurn);
+        }
+        if !is_legal(&self.board, registry, played) {
+            return Err(NetworkError::Illegal);
+        }
+        let ply = self.moves.len();
+        self.apply(played);
+        self.send(&Message::Move { ply, played });
+        Ok(())
+    }
+
+    pub fn resign(&mut self) -> Result<(), NetworkError> {
+        let player = self.check_playing()?;
+        self.outcome = Some(Outcome::Resigned(player));
+        self.send(&Message::Resign);
+        Ok(())
+    }
+
+    pub fn offer_draw(&mut self) -> Result<(), NetworkError> {
+        let player = self.check_playing()?;
+        if self.draw_offer.is_none() {
+            self.draw_offer = Some(player);
+            self.send(&Message::OfferDraw);
+        }
+        Ok(())
+    }
+
+    /// accept or decline the offer of the opponent, nothing happens when there is none
+    pub fn answer_draw(&mut self, accept: bool) -> Result<(), NetworkError> {
+        let player = self.check_playing()?;
+        if self.draw_offer == Some(player.opponent()) {
+            self.draw_offer = None;
+            if accept {
+                self.outcome = Some(Outcome::DrawAgreed);
+            }
+            self.send(&Message::AnswerDraw { accept });
+        }
+        Ok(())
+    }
+
+    fn apply(&mut self, played: Move) {
+        self.board.move_piece(played);
+        self.moves.push(played);
+        self.draw_offer = None;
+    }
+
+    /// a failed write shuts the stream down, the reading thread then reports it closed
+    fn send(&mut self, message: &Message) {
+        if let Some((_, stream)) = &mut self.connection {
+            let mut line = serde_json::to_string(message).expect("messages can always be serialized");
+            line.push('\n');
+            if let Err(error) = stream.write_all(line.as_bytes()) {
+                log::warn!("couldn't send to the opponent: {error}");
+                let _ = stream.shutdown(Shutdown::Both);
+            }
+        }
+    }
+
+    fn drop_connection(&mut self) {
+        if let Some((_, stream)) = &self.connection {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+
+    /// handle what the opponent sent since the last call, to be called every frame
+    pub fn poll(&mut self, registry: &PieceRegistry) -> Vec<NetworkEvent> {
+        let mut events = Vec::new();
+        while let Ok(link) = self.links.try_recv() {
+            let current = self.connection.as_ref().map(|(id, _)| *id);
+            match link {
+                Link::Connected(_, stream) if self.ready => {
+                    // the seat is taken until the guest playing leaves
+                    log::info!("refusing a connection, the opponent is already connected");
+                    let _ = stream.shutdown(Shutdown::Both);
+                }
+                Link::Connected(id, stream) => {
+                    // a guest connecting again replaces a connection that didn't say hello
+                    self.drop_connection();
+                    self.connection = Some((id, stream));
+                    self.ready = false;
+                    self.send(&Message::Hello { version: PROTOCOL_VERSION, variant: self.variant.clone() });
+                }
+                Link::Closed(id) if Some(id) == current => {
+                    self.connection = None;
+                    if std::mem::take(&mut self.ready) {
+                        events.push(NetworkEvent::Disconnected);
+                    }
+                }
+                Link::Received(id, message) if Some(id) == current => {
+                    if let Err(reason) = self.receive(registry, message, &mut events) {
+                        log::warn!("disconnecting the opponent: {reason}");
+                        self.drop_connection();
+                        events.push(NetworkEvent::Refused(reason));
+                    }
+                }
+                _ => {}
+            }
+        }
+        events
+    }
+
+    fn receive(&mut self, registry: &PieceRegistry, message: Message, events: &mut Vec<NetworkEvent>) -> Result<(), String> {
+        let opponent = self.player.map(Player::opponent);
+        match message {
+            Message::Hello { version, variant } => {
+                if version != PROTOCOL_VERSION || variant != self.variant {
+                    if self.role == Role::Guest {
+                        // joining again would only be refused again
+                        self.stop.store(true, Ordering::Relaxed);
+                    }
+                    return Err(format!("the opponent plays version {version} with other pieces or rules"));
+                }
+                if self.role == Role::Host {
+                    let guest = self.player.map_or(Player::Black, Player::opponent);
+                    let sync = Message::Sync { guest, moves: self.moves.clone(), outcome: self.outcome, draw_offer: self.draw_offer };
+                    self.send(&sync);
+                    self.ready = true;
+                    events.push(NetworkEvent::Connected);
+                }
+            }
+            Message::Sync { guest, moves, outcome, draw_offer } if self.role == Role::Guest => {
+                let mut board = ChessBoard::new();
+                for &played in &moves {
+                    if !is_legal(&board, registry, played) {
+                        return Err("the game of the host has an illegal move".to_string());
+                    }
+                    board.move_piece(played);
+                }
+                self.player = Some(guest);
+                self.board = board;
+                self.moves = moves;
+                self.outcome = outcome;
+                self.draw_offer = draw_offer;
+                self.ready = true;
+                events.push(NetworkEvent::Synced);
+            }
+            _ if !self.ready => return Err("the opponent didn't say hello".to_string()),
+            Message::Move { ply, played } => {
+                if ply != self.moves.len() || self.outcome.is_some() || opponent != Some(self.board.turn()) || !is_legal(&self.board, registry, played) {
+                    return Err(format!("the opponent played an illegal move at ply {ply}"));
+                }
+                self.apply(played);
+                events.push(NetworkEvent::Moved(played));
+            }
+            Message::Resign => {
+                if let Some(opponent) = opponent.filter(|_| self.outcome.is_none()) {
+                    self.outcome = Some(Outcome::Resigned(opponent));
+                    events.push(NetworkEvent::GameOver(Outcome::Resigned(opponent)));
+                }
+            }
+            Message::OfferDraw => {
+                if self.outcome.is_none() && self.draw_offer.is_none() {
+                    self.draw_offer = opponent;
+                    events.push(NetworkEvent::DrawOffered);
+                }
+            }
+            Message::AnswerDraw { accept } => {
+                if self.draw_offer.is_some() && self.draw_offer == self.player && self.outcome.is_none() {
+                    self.draw_offer = None;
+                    if accept {
+                        self.outcome = Some(Outcome::DrawAgreed);
+                        events.push(NetworkEvent::GameOver(Outcome::DrawAgreed));
+                    } else {
+                        events.push(NetworkEvent::DrawDeclined);
+                    }
+                }
+            }
+            Message::Sync { .. } => return Err("only the host sends the game".to_string()),
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        self.drop_connection();
+    }
+}
+
+/// the same check as a click on the board: the piece belongs to the side to move, can go there
+/// and doesn't leave its royal piece attacked
+pub fn is_legal(board: &ChessBoard, registry: &PieceRegistry, played: Move) -> bool {
+    board.legal_moves(registry).any(|candidate| candidate == played)
+}
+
+/// hand a clone of the stream to the session, none once the session is gone
+fn connected(id: u64, stream: TcpStream, sender: &Sender<Link>) -> Option<TcpStream> {
+    let _ = stream.set_nodelay(true);
+    let writer = stream.try_clone().ok()?;
+    sender.send(Link::Connected(id, writer)).ok()?;
+    Some(stream)
+}
+
+fn accept(id: u64, stream: TcpStream, sender: &Sender<Link>) -> Option<()> {
+    let reader = connected(id, stream, sender)?;
+    let sender = sender.clone();
+    thread::spawn(move || read_messages(id, reader, &sender));
+    Some(())
+}
+
+/// forward the messages until the connection closes or something unreadable comes in
+fn read_messages(id: u64, stream: TcpStream, sender: &Sender<Link>) {
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        match serde_json::from_str(&line) {
+            Ok(message) => {
+                if sender.send(Link::Received(id, message)).is_err() {
+                    return;
+                }
+            }
+            Err(error) => {
+                log::warn!("unreadable message from the opponent: {error}");
+                break;
+            }
+        }
+    }
+    let _ = sender.send(Link::Closed(id));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::time::Instant;
+    use super::*;
+    use crate::game::config::Config;
+    use crate::game::notation;
+    use chess_rules::MoveKind;
+
+    fn registry() -> PieceRegistry {
+        let path = Path::new("config");
+        PieceRegistry::from_config(&Config::load(path).unwrap().pieces)
+    }
+
+    /// poll until an event matching `wanted` comes, the events before it are returned too
+    fn wait_for(session: &mut Session, registry: &PieceRegistry, wanted: impl Fn(&NetworkEvent) -> bool) -> Vec<NetworkEvent> {
+        let start = Instant::now();
+        let mut events = Vec::new();
+        while start.elapsed() < Duration::from_secs(5) {
+            events.extend(session.poll(registry));
+            if events.iter().any(&wanted) {
+                return events;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("timed out, got {events:?}");
+    }
+
+    fn host(registry: &PieceRegistry) -> Session {
+        Session::host("127.0.0.1:0", Player::White, registry.piece_names()).unwrap()
+    }
+
+    fn join(host: &mut Session, registry: &PieceRegistry) -> Session {
+        let mut guest = Session::join(host.local_addr().unwrap(), registry.piece_names());
+        // both ends say hello as soon as they see the connection, so they are polled in turn
+        let start = Instant::now();
+        while !(host.is_connected() && guest.is_connected()) {
+            assert!(start.elapsed() < Duration::from_secs(5), "the guest couldn't join");
+            host.poll(registry);
+            guest.poll(registry);
+            thread::sleep(Duration::from_millis(10));
+        }
+        guest
+    }
+
+    fn parse(session: &Session, registry: &PieceRegistry, text: &str) -> Move {
+        let board = session.moves().iter().fold(ChessBoard::new(), |mut board, played| {
+            board.move_piece(*played);
+            board
+        });
+        notation::parse_move(&board, registry, text).unwrap()
+    }
+
+    /// a raw connection speaking the protocol by hand, as a misbehaving guest would
+    fn raw_guest(host: &Session, hello: &Message) -> (TcpStream, BufReader<TcpStream>) {
+        let mut stream = TcpStream::connect(host.local_addr().unwrap()).unwrap();
+        stream.write_all(format!("{}\n", serde_json::to_string(hello).unwrap()).as_bytes()).unwrap();
+        let reader = BufReader::new(stream.try_clone().unwrap());
+        (stream, reader)
+    }
+
+    #[test]
+    fn moves_are_played_on_both_ends() {
+        let registry = registry();
+        let mut host = host(&registry);
+        let mut guest = join(&mut host, &registry);
+        assert_eq!(guest.player(), Some(Player::Black));
+
+        let e4 = parse(&host, &registry, "e4");
+        host.play(&registry, e4).unwrap();
+        wait_for(&mut guest, &registry, |event| *event == NetworkEvent::Moved(e4));
+
+        let e5 = parse(&guest, &registry, "e5");
+        guest.play(&registry, e5).unwrap();
+        wait_for(&mut host, &registry, |event| *event == NetworkEvent::Moved(e5));
+        assert_eq!(host.moves(), &[e4, e5]);
+        assert_eq!(guest.moves(), &[e4, e5]);
+    }
+
+    #[test]
+    fn each_side_only_moves_its_own_pieces() {
+        let registry = registry();
+        let mut host = host(&registry);
+        let mut guest = join(&mut host, &registry);
+
+        let e4 = parse(&host, &registry, "e4");
+        assert_eq!(guest.play(&registry, e4), Err(NetworkError::NotYourTurn));
+        let mut too_far = e4;
+        too_far.to = (4, 3);
+        assert_eq!(host.play(&registry, too_far), Err(NetworkError::Illegal));
+        assert!(host.moves().is_empty() && guest.moves().is_empty());
+    }
+
+    #[test]
+    fn moves_leaving_the_king_attacked_are_illegal() {
+        let registry = registry();
+        let mut host = host(&registry);
+        let mut guest = join(&mut host, &registry);
+        for text in ["e4", "e5", "d4", "Bb4"] {
+            let (mover, waiter) = if host.can_move() { (&mut host, &mut guest) } else { (&mut guest, &mut host) };
+            let played = parse(mover, &registry, text);
+            mover.play(&registry, played).unwrap();
+            wait_for(waiter, &registry, |event| *event == NetworkEvent::Moved(played));
+        }
+        // the bishop gives check, pushing the h pawn doesn't answer it
+        let mut board = ChessBoard::new();
+        host.moves().iter().for_each(|&played| board.move_piece(played));
+        let h3 = board.get_move((7, 6), (7, 5), MoveKind::Quiet).unwrap();
+        assert_eq!(host.play(&registry, h3), Err(NetworkError::Illegal));
+    }
+
+    #[test]
+    fn moves_are_checked_on_arrival() {
+        let registry = registry();
+        let mut host = host(&registry);
+        let (mut stream, mut reader) = raw_guest(&host, &Message::Hello { version: PROTOCOL_VERSION, variant: registry.piece_names() });
+        wait_for(&mut host, &registry, |event| *event == NetworkEvent::Connected);
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+        assert!(matches!(serde_json::from_str(&line).unwrap(), Message::Sync { guest: Player::Black, .. }));
+
+        // black moving first
+        let mut e5 = parse(&host, &registry, "e4");
+        e5.from = (4, 1);
+        e5.to = (4, 3);
+        e5.piece.player = Player::Black;
+        let played = Message::Move { ply: 0, played: e5 };
+        stream.write_all(format!("{}\n", serde_json::to_string(&played).unwrap()).as_bytes()).unwrap();
+        wait_for(&mut host, &registry, |event| matches!(event, NetworkEvent::Refused(_)));
+        assert!(host.moves().is_empty());
+    }
+
+    #[test]
+    fn other_versions_are_refused() {
+        let registry = registry();
+        let mut host = host(&registry);
+        let _guest = raw_guest(&host, &Message::Hello { version: PROTOCOL_VERSION + 1, variant: registry.piece_names() });
+        wait_for(&mut host, &registry, |event| matches!(event, NetworkEvent::Refused(_)));
+        assert!(!host.is_connected());
+    }
+
+    #[test]
+    fn a_second_guest_is_refused() {
+        let registry = registry();
+        let mut host = host(&registry);
+        let mut guest = join(&mut host, &registry);
+
+        let (_stream, mut reader) = raw_guest(&host, &Message::Hello { version: PROTOCOL_VERSION, variant: registry.piece_names() });
+        let start = Instant::now();
+        let mut line = String::new();
+        reader.get_ref().set_read_timeout(Some(Duration::from_millis(10))).unwrap();
+        // the host only looks at new connections when polled
+        while reader.read_line(&mut line).is_err() {
+            assert!(start.elapsed() < Duration::from_secs(5), "the second guest wasn't refused");
+            assert!(host.poll(&registry).is_empty());
+        }
+        assert!(line.is_empty(), "the second guest got {line}");
+
+        let e4 = parse(&host, &registry, "e4");
+        host.play(&registry, e4).unwrap();
+        wait_for(&mut guest, &registry, |event| *event == NetworkEvent::Moved(e4));
+    }
+
+    #[test]
+    fn resigning_ends_the_game() {
+        let registry = registry();
+        let mut host = host(&registry);
+        let mut guest = join(&mut host, &registry);
+
+        guest.resign().unwrap();
+        let resigned = NetworkEvent::GameOver(Outcome::Resigned(Player::Black));
+        wait_for(&mut host, &registry, |event| *event == resigned);
+        assert_eq!(host.outcome().map(Outcome::result), Some("1-0"));
+        let e4 = parse(&host, &registry, "e4");
+        assert_eq!(host.play(&registry, e4), Err(NetworkError::GameOver));
+    }
+
+    #[test]
+    fn draws_are_offered_and_answered() {
+        let registry = registry();
+        let mut host = host(&registry);
+        let mut guest = join(&mut host, &registry);
+
+        host.offer_draw().unwrap();
+        wait_for(&mut guest, &registry, |event| *event == NetworkEvent::DrawOffered);
+        guest.answer_draw(false).unwrap();
+        wait_for(&mut host, &registry, |event| *event == NetworkEvent::DrawDeclined);
+        assert_eq!(host.draw_offer(), None);
+
+        guest.offer_draw().unwrap();
+        wait_for(&mut host, &registry, |event| *event == NetworkEvent::DrawOffered);
+        host.answer_draw(true).unwrap();
+        wait_for(&mut guest, &registry, |event| *event == NetworkEvent::GameOver(Outcome::DrawAgreed));
+        assert_eq!(host.outcome(), Some(Outcome::DrawAgreed));
+    }
+
+    #[test]
+    fn a_guest_joining_again_gets_the_whole_game() {
+        let registry = registry();
+        let mut host = host(&registry);
+        let mut guest = join(&mut host, &registry);
+        for text in ["e4", "e5", "Nf3"] {
+            let (mover, waiter) = if host.can_move() { (&mut host, &mut guest) } else { (&mut guest, &mut host) };
+            let played = parse(mover, &registry, text);
+            mover.play(&registry, played).unwrap();
+            wait_for(waiter, &registry, |event| *event == NetworkEvent::Moved(played));
+        }
+
+        drop(guest);
+        wait_for(&mut host, &registry, |event| *event == NetworkEvent::Disconnected);
+        let e4 = host.moves()[0];
+        assert_eq!(host.play(&registry, e4), Err(NetworkError::NotConnected));
+
+        let guest = join(&mut host, &registry);
+        assert_eq!(guest.moves(), host.moves());
+        assert_eq!(guest.moves().len(), 3);
+        assert!(guest.can_move());
+    }
+}
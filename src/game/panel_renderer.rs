@@ -0,0 +1,266 @@
+use vello::kurbo::{Affine, Point, Rect, RoundedRect, Size};
+use vello::peniko::{Color, Fill, Mix};
+use vello::Scene;
+use crate::game::board_renderer::BoardRenderer;
+use crate::game::chess_board::{Piece, Player};
+use crate::game::clock::{self, Clocks};
+use crate::game::history::History;
+use crate::game::move_entry::MoveEntry;
+use crate::game::sprites::PieceSprites;
+use crate::game::text::{Align, TextRenderer};
+
+/// A line of the move list: a move for each player, or a variation that could have been played instead.
+pub enum MoveListRow {
+    Moves { number: usize, plies: [Option<usize>; 2] }, //indices of the plies in the displayed line
+    Variation { path: Vec<usize>, text: String },
+}
+
+/// Draws what goes around the board: the captured pieces, the material balance and the move list.
+pub struct PanelRenderer {
+    text: TextRenderer,
+}
+
+impl PanelRenderer {
+    const TRAY_HEIGHT: f64 = 50.0;
+    const PIECE_SCALE: f64 = Self::TRAY_HEIGHT / BoardRenderer::CELL_SIZE;
+    const PIECE_SPACING: f64 = 28.0; //captured pieces overlap a little to fit in the panel
+    const MARGIN: f64 = 10.0;
+    const TEXT_SIZE: f32 = 28.0;
+    const ROW_HEIGHT: f64 = 36.0;
+    const MOVE_SIZE: f32 = 24.0;
+    const NUMBER_WIDTH: f64 = 64.0;
+    const VARIATION_SIZE: f32 = 20.0;
+    const ERROR_COLOR: Color = Color::rgb8(255, 110, 100);
+    const CLOCK_WIDTH: f64 = 130.0;
+
+    pub fn new() -> Self {
+        Self {
+            text: TextRenderer::new(),
+        }
+    }
+
+    /// the trays are laid out like the board, pieces taken by the player on top are drawn at the top of the panel
+    pub fn draw_captured(&self, captured: [(Player, &[Piece], i32); 2], top: Player, size: Size, sprites: &PieceSprites, scene: &mut Scene) {
+        for (player, pieces, advantage) in captured {
+            let y = Self::tray_y(player, top, size);
+
+            let mut x = Self::MARGIN;
+            for piece in pieces {
+                let affine = Affine::translate((x, y)) * Affine::scale(Self::PIECE_SCALE);
+                scene.append(sprites.get_sprite(piece.piece_kind, piece.player), Some(affine));
+                x += Self::PIECE_SPACING;
+            }
+
+            if advantage > 0 {
+                let baseline = y + (Self::TRAY_HEIGHT + self.text.cap_height(Self::TEXT_SIZE)) / 2.0;
+                let position = (x + Self::TRAY_HEIGHT - Self::PIECE_SPACING + Self::MARGIN, baseline);
+                self.text.draw(scene, &format!("+{advantage}"), Self::TEXT_SIZE, Align::Start, Color::WHITE, Affine::translate(position));
+            }
+        }
+    }
+
+    fn tray_y(player: Player, top: Player, size: Size) -> f64 {
+        if player == top {
+            Self::MARGIN
+        } else {
+            size.height - Self::MARGIN - Self::TRAY_HEIGHT
+        }
+    }
+
+    /// the clocks sit at the end of the trays, the one counting down is highlighted
+    pub fn draw_clocks(&self, clocks: &Clocks, turn: Player, top: Player, size: Size, scene: &mut Scene) {
+        for player in [Player::White, Player::Black] {
+            let y = Self::tray_y(player, top, size);
+            let rect = Rect::new(size.width - Self::MARGIN - Self::CLOCK_WIDTH, y, size.width - Self::MARGIN, y + Self::TRAY_HEIGHT);
+            if clocks.is_running() && player == turn {
+                let highlight = RoundedRect::from_rect(rect, 6.0);
+                scene.fill(Fill::NonZero, Affine::IDENTITY, Color::rgba8(255, 255, 255, 60), None, &highlight);
+            }
+            let color = if clocks.flagged() == Some(player) { Self::ERROR_COLOR } else { Color::WHITE };
+            let baseline = y + (Self::TRAY_HEIGHT + self.text.cap_height(Self::TEXT_SIZE)) / 2.0;
+            let position = (rect.x1 - Self::MARGIN, baseline);
+            self.text.draw(scene, &clock::format(clocks.remaining(player)), Self::TEXT_SIZE, Align::End, color, Affine::translate(position));
+        }
+    }
+
+    /// the space left between the two trays
+    fn move_list_area(size: Size) -> Rect {
+        let margin = 2.0 * Self::MARGIN + Self::TRAY_HEIGHT;
+        Rect::new(Self::MARGIN, margin, size.width - Self::MARGIN, (size.height - margin).max(margin))
+    }
+
+    pub fn visible_rows(size: Size) -> usize {
+        (Self::move_list_area(size).height() / Self::ROW_HEIGHT) as usize
+    }
+
+    /// lay out the displayed line two moves per row, each followed by the variations branching from its moves
+    pub fn move_list_rows(history: &History) -> Vec<MoveListRow> {
+        let line = history.line();
+        let mut rows = Vec::new();
+        let mut row: Option<(usize, [Option<usize>; 2])> = None;
+
+        for (ply, entry) in line.iter().enumerate().skip(1) {
+            let number = ply.div_ceil(2);
+            let column = match entry.board.turn().opponent() {
+                Player::White => 0,
+                Player::Black => 1,
+            };
+            match &mut row {
+                Some((row_number, plies)) if *row_number == number && plies[column].is_none() => plies[column] = Some(ply),
+                _ => {
+                    if let Some((number, plies)) = row.take() {
+                        Self::push_row(history, number, plies, &mut rows);
+                    }
+                    let mut plies = [None; 2];
+                    plies[column] = Some(ply);
+                    row = Some((number, plies));
+                }
+            }
+        }
+        if let Some((number, plies)) = row {
+            Self::push_row(history, number, plies, &mut rows);
+        }
+        rows
+    }
+
+    fn push_row(history: &History, number: usize, plies: [Option<usize>; 2], rows: &mut Vec<MoveListRow>) {
+        rows.push(MoveListRow::Moves { number, plies });
+        for ply in plies.into_iter().flatten() {
+            let parent = history.line_path(ply - 1);
+            let chosen = history.line_path(ply)[ply - 1];
+            for index in (0..history.alternatives(ply)).filter(|&index| index != chosen) {
+                let mut path = parent.to_vec();
+                path.push(index);
+                let text = Self::variation_preview(history, &path);
+                rows.push(MoveListRow::Variation { path, text });
+            }
+        }
+    }
+
+    /// the first moves of a variation, following its main continuation
+    fn variation_preview(history: &History, path: &[usize]) -> String {
+        const PREVIEW_LENGTH: usize = 6;
+        let mut node = history.node(path);
+        let mut text = String::new();
+        for (index, depth) in (path.len()..).take(PREVIEW_LENGTH).enumerate() {
+            let san = node.ply.san.as_deref().unwrap_or_default();
+            let number = depth.div_ceil(2);
+            match node.ply.board.turn().opponent() {
+                Player::White => text += &format!("{number}. {san} "),
+                Player::Black if index == 0 => text += &format!("{number}... {san} "),
+                Player::Black => text += &format!("{san} "),
+            }
+            let Some(next) = node.children.first() else {
+                return text;
+            };
+            node = next;
+        }
+        text + "…"
+    }
+
+    /// the row holding the displayed ply
+    pub fn cursor_row(rows: &[MoveListRow], cursor: usize) -> usize {
+        rows.iter()
+            .position(|row| matches!(row, MoveListRow::Moves { plies, .. } if plies.contains(&Some(cursor))))
+            .unwrap_or(0)
+    }
+
+    fn row_rect(area: Rect, row: usize, scroll: usize) -> Rect {
+        let y = area.y0 + (row as f64 - scroll as f64) * Self::ROW_HEIGHT;
+        Rect::new(area.x0, y, area.x1, y + Self::ROW_HEIGHT)
+    }
+
+    fn move_rect(row_rect: Rect, column: usize) -> Rect {
+        let column_width = (row_rect.width() - Self::NUMBER_WIDTH) / 2.0;
+        let x = row_rect.x0 + Self::NUMBER_WIDTH + column as f64 * column_width;
+        Rect::new(x, row_rect.y0, x + column_width, row_rect.y1)
+    }
+
+    /// the path to the move written under `point`
+    pub fn target_at(point: Point, size: Size, scroll: usize, rows: &[MoveListRow], history: &History) -> Option<Vec<usize>> {
+        let area = Self::move_list_area(size);
+        if !area.contains(point) {
+            return None;
+        }
+        let row = scroll + ((point.y - area.y0) / Self::ROW_HEIGHT) as usize;
+        match rows.get(row)? {
+            MoveListRow::Moves { plies, .. } => {
+                let row_rect = Self::row_rect(area, row, scroll);
+                let column = (0..2).find(|&column| Self::move_rect(row_rect, column).contains(point))?;
+                plies[column].map(|ply| history.line_path(ply).to_vec())
+            }
+            MoveListRow::Variation { path, .. } => Some(path.clone()),
+        }
+    }
+
+    /// the rows starting from the `scroll`th one, with the move leading to the displayed position highlighted
+    pub fn draw_move_list(&self, rows: &[MoveListRow], history: &History, scroll: usize, size: Size, scene: &mut Scene) {
+        let area = Self::move_list_area(size);
+        scene.push_layer(Mix::Clip, 1.0, Affine::IDENTITY, &area);
+
+        let line = history.line();
+        let text_offset = (Self::ROW_HEIGHT + self.text.cap_height(Self::MOVE_SIZE)) / 2.0;
+        for (index, row) in rows.iter().enumerate().skip(scroll) {
+            let row_rect = Self::row_rect(area, index, scroll);
+            if row_rect.y0 > area.y1 {
+                break;
+            }
+            match row {
+                MoveListRow::Moves { number, plies } => {
+                    let position = (area.x0 + Self::NUMBER_WIDTH - Self::MARGIN, row_rect.y0 + text_offset);
+                    self.text.draw(scene, &format!("{number}."), Self::MOVE_SIZE, Align::End, Color::LIGHT_GRAY, Affine::translate(position));
+
+                    for (column, ply) in plies.iter().enumerate() {
+                        let rect = Self::move_rect(row_rect, column);
+                        let Some(ply) = *ply else {
+                            let position = (rect.x0 + Self::MARGIN, rect.y0 + text_offset);
+                            self.text.draw(scene, "…", Self::MOVE_SIZE, Align::Start, Color::LIGHT_GRAY, Affine::translate(position));
+                            continue;
+                        };
+                        if ply == history.cursor() {
+                            let highlight = RoundedRect::from_rect(rect.inset(-2.0), 6.0);
+                            scene.fill(Fill::NonZero, Affine::IDENTITY, Color::rgba8(255, 255, 255, 60), None, &highlight);
+                        }
+                        let san = line[ply].san.as_deref().unwrap_or_default();
+                        let position = (rect.x0 + Self::MARGIN, rect.y0 + text_offset);
+                        self.text.draw(scene, san, Self::MOVE_SIZE, Align::Start, Color::WHITE, Affine::translate(position));
+                    }
+                }
+                MoveListRow::Variation { text, .. } => {
+                    let position = (area.x0 + Self::NUMBER_WIDTH, row_rect.y0 + text_offset);
+                    self.text.draw(scene, text, Self::VARIATION_SIZE, Align::Start, Color::LIGHT_GRAY, Affine::translate(position));
+                }
+            }
+        }
+        scene.pop_layer();
+    }
+
+    /// the typed move in a box over the bottom of the move list, with the reason it was refused below it
+    pub fn draw_move_entry(&self, entry: &MoveEntry, size: Size, scene: &mut Scene) {
+        let area = Self::move_list_area(size);
+        let rows = if entry.error.is_some() { 2.0 } else { 1.0 };
+        let rect = Rect::new(area.x0, area.y1 - rows * Self::ROW_HEIGHT, area.x1, area.y1);
+        let background = RoundedRect::from_rect(rect, 6.0);
+        scene.fill(Fill::NonZero, Affine::IDENTITY, Color::rgba8(0, 0, 0, 200), None, &background);
+
+        let text_offset = (Self::ROW_HEIGHT + self.text.cap_height(Self::MOVE_SIZE)) / 2.0;
+        let position = (rect.x0 + Self::MARGIN, rect.y0 + text_offset);
+        self.text.draw(scene, &format!("> {}_", entry.text), Self::MOVE_SIZE, Align::Start, Color::WHITE, Affine::translate(position));
+        if let Some(error) = &entry.error {
+            let position = (rect.x0 + Self::MARGIN, rect.y0 + Self::ROW_HEIGHT + text_offset);
+            self.text.draw(scene, error, Self::VARIATION_SIZE, Align::Start, Self::ERROR_COLOR, Affine::translate(position));
+        }
+    }
+
+    /// a line of text in a box over the bottom of the move list, like the move entry
+    pub fn draw_status(&self, status: &str, size: Size, scene: &mut Scene) {
+        let area = Self::move_list_area(size);
+        let rect = Rect::new(area.x0, area.y1 - Self::ROW_HEIGHT, area.x1, area.y1);
+        let background = RoundedRect::from_rect(rect, 6.0);
+        scene.fill(Fill::NonZero, Affine::IDENTITY, Color::rgba8(0, 0, 0, 200), None, &background);
+
+        let text_offset = (Self::ROW_HEIGHT + self.text.cap_height(Self::VARIATION_SIZE)) / 2.0;
+        let position = (rect.x0 + Self::MARGIN, rect.y0 + text_offset);
+        self.text.draw(scene, status, Self::VARIATION_SIZE, Align::Start, Color::WHITE, Affine::translate(position));
+    }
+}
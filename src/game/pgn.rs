@@ -0,0 +1,274 @@
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+use crate::game::annotation::Annotations;
+use crate::game::chess_board::{ChessBoard, Player};
+use crate::game::clock;
+use crate::game::fen::{self, FenError};
+use crate::game::history::{History, Node, Ply};
+use crate::game::notation::{self, MoveEntryError};
+use crate::game::piece_registry::PieceRegistry;
+
+const LINE_WIDTH: usize = 80;
+
+/// Export the game in the PGN format, with the variations nested in parentheses.
+/// Annotations and clock times are written as comments after the move they follow.
+/// `result` is `1-0`, `0-1`, `1/2-1/2` or `*` while the game goes on.
+/// Games that don't start from the standard position get `SetUp` and `FEN` tags.
+pub fn export(history: &History, registry: &PieceRegistry, result: &str) -> String {
+    let root = history.root();
+    let start = fen::write(&root.ply.board, registry, history.full_move());
+    let mut tags = vec![
+        ("Event", "Casual game"),
+        ("Site", "?"),
+        ("Date", "????.??.??"),
+        ("Round", "?"),
+        ("White", "?"),
+        ("Black", "?"),
+        ("Result", result),
+    ];
+    if start != fen::STARTING_POSITION {
+        tags.extend([("SetUp", "1"), ("FEN", start.as_str())]);
+    }
+    let mut pgn: String = tags.iter().map(|(name, value)| format!("[{name} \"{value}\"]\n")).collect();
+    pgn.push('\n');
+
+    // plies are counted from the first white move of the game, even when it started later
+    let played_before = 2 * (history.full_move() - 1) + (root.ply.board.turn() == Player::Black) as usize;
+    let mut tokens = Vec::new();
+    push_comment(&root.ply, &mut tokens);
+    push_continuation(root, played_before + 1, true, &mut tokens);
+    tokens.push(result.to_string());
+
+    let mut line_length = 0;
+    let mut after_parenthesis = false;
+    for token in tokens {
+        // parentheses stick to the moves they enclose
+        let glued = after_parenthesis || token == ")";
+        if line_length > 0 && line_length + 1 + token.len() > LINE_WIDTH {
+            pgn.push('\n');
+            line_length = 0;
+        } else if line_length > 0 && !glued {
+            pgn.push(' ');
+            line_length += 1;
+        }
+        after_parenthesis = token == "(";
+        line_length += token.len();
+        pgn += &token;
+    }
+    pgn.push('\n');
+    pgn
+}
+
+fn push_comment(ply: &Ply, tokens: &mut Vec<String>) -> bool {
+    let mut commands = Vec::new();
+    if !ply.annotations.is_empty() {
+        commands.push(ply.annotations.to_pgn_commands());
+    }
+    if let Some(time) = ply.clock {
+        commands.push(format!("[%clk {}]", clock::pgn_format(time)));
+    }
+    if commands.is_empty() {
+        return false;
+    }
+    tokens.push(format!("{{ {} }}", commands.join(" ")));
+    true
+}
+
+/// `depth` is the index of the children's ply from the first white move of the game.
+/// The first child is the main move, the others are written as variations right after it.
+fn push_continuation(node: &Node, depth: usize, needs_number: bool, tokens: &mut Vec<String>) {
+    let Some((main, variations)) = node.children.split_first() else {
+        return;
+    };
+    let mut needs_number = push_move(&main.ply, depth, needs_number, tokens);
+
+    for variation in variations {
+        tokens.push("(".to_string());
+        let commented = push_move(&variation.ply, depth, true, tokens);
+        push_continuation(variation, depth + 1, commented, tokens);
+        tokens.push(")".to_string());
+        needs_number = true;
+    }
+
+    // the main line goes on with a reminder of the move number if something interrupted it
+    let mut node = main;
+    let mut depth = depth + 1;
+    while let Some((next, others)) = node.children.split_first() {
+        if !others.is_empty() {
+            push_continuation(node, depth, needs_number, tokens);
+            return;
+        }
+        needs_number = push_move(&next.ply, depth, needs_number, tokens);
+        node = next;
+        depth += 1;
+    }
+}
+
+/// returns whether the next move needs its number, because a comment was written after this one.
+/// A move by black needs one when it doesn't directly follow the white move
+fn push_move(ply: &Ply, depth: usize, needs_number: bool, tokens: &mut Vec<String>) -> bool {
+    let Some(san) = &ply.san else {
+        return needs_number;
+    };
+    // the player who made the move is the one who isn't to move anymore
+    let number = depth.div_ceil(2);
+    match ply.board.turn().opponent() {
+        Player::White => tokens.push(format!("{number}.")),
+        Player::Black if needs_number => tokens.push(format!("{number}...")),
+        Player::Black => {}
+    }
+    tokens.push(san.clone());
+    push_comment(ply, tokens)
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum PgnError {
+    Fen(FenError),
+    Move { ply: usize, san: String, error: MoveEntryError },
+    UnbalancedVariation,
+}
+
+impl Display for PgnError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PgnError::Fen(error) => write!(f, "invalid FEN tag: {error}"),
+            PgnError::Move { ply, san, error } => write!(f, "{error} `{san}` at ply {ply}"),
+            PgnError::UnbalancedVariation => write!(f, "unbalanced variation parentheses"),
+        }
+    }
+}
+
+impl std::error::Error for PgnError {}
+
+/// Read the first game of a PGN file, with its variations and the arrows, circles and clock times of its comments.
+/// A `FEN` tag sets the starting position, the other tags are ignored.
+pub fn import(pgn: &str, registry: &PieceRegistry) -> Result<History, PgnError> {
+    let mut movetext = pgn.trim_start();
+    let mut board = ChessBoard::new();
+    let mut full_move = 1;
+    while movetext.starts_with('[') {
+        let end = movetext.find(']').unwrap_or(movetext.len() - 1);
+        let tag = &movetext[1..end];
+        if let Some((name, value)) = tag.split_once(char::is_whitespace) {
+            if name == "FEN" {
+                let value = value.trim().trim_matches('"');
+                board = fen::parse(value, registry).map_err(PgnError::Fen)?;
+                full_move = value.split_whitespace().nth(5).and_then(|number| number.parse().ok()).filter(|&number| number > 0).unwrap_or(1);
+            }
+        }
+        movetext = movetext[end + 1..].trim_start();
+    }
+
+    let mut history = History::starting_at(board, full_move);
+    let mut variations = Vec::new(); //where to come back after each open variation
+    let mut characters = movetext.char_indices().peekable();
+    while let Some((start, character)) = characters.next() {
+        match character {
+            '{' => {
+                let end = movetext[start..].find('}').map_or(movetext.len(), |end| start + end);
+                apply_comment(&movetext[start + 1..end], history.current_mut());
+                while characters.next_if(|&(index, _)| index <= end).is_some() {}
+            }
+            ';' => while characters.next_if(|&(_, character)| character != '\n').is_some() {},
+            '(' => {
+                variations.push(history.path().to_vec());
+                history.back();
+            }
+            ')' => {
+                let path = variations.pop().ok_or(PgnError::UnbalancedVariation)?;
+                history.go_to_path(&path);
+            }
+            character if character.is_whitespace() => {}
+            _ => {
+                let mut end = start + character.len_utf8();
+                while let Some(&(index, character)) = characters.peek() {
+                    if character.is_whitespace() || "{}();".contains(character) {
+                        break;
+                    }
+                    end = index + character.len_utf8();
+                    characters.next();
+                }
+                let token = &movetext[start..end];
+                if matches!(token, "1-0" | "0-1" | "1/2-1/2" | "*") {
+                    break;
+                }
+                // move numbers may be glued to their move, as in `1.e4` or `12...0-0`
+                let san = match token.split_once('.') {
+                    Some((number, rest)) if number.chars().all(|character| character.is_ascii_digit()) => rest.trim_start_matches('.'),
+                    _ => token,
+                };
+                if san.is_empty() || san.starts_with('$') {
+                    continue;
+                }
+                let board = history.current().board;
+                let played = notation::parse_move(&board, registry, san).map_err(|error| PgnError::Move {
+                    ply: history.cursor() + 1,
+                    san: san.to_string(),
+                    error,
+                })?;
+                let san = notation::san(&board, registry, played);
+                let mut after = board;
+                after.move_piece(played);
+                history.play(Ply::new(after, Some(san), played.captured));
+            }
+        }
+    }
+    if !variations.is_empty() {
+        return Err(PgnError::UnbalancedVariation);
+    }
+    history.go_to(0);
+    Ok(history)
+}
+
+fn apply_comment(comment: &str, ply: &mut Ply) {
+    let annotations = Annotations::from_pgn_commands(comment);
+    ply.annotations.arrows.extend(annotations.arrows);
+    ply.annotations.circles.extend(annotations.circles);
+    if let Some(time) = comment.find("[%clk ").and_then(|start| parse_clock(&comment[start + 6..])) {
+        ply.clock = Some(time);
+    }
+}
+
+/// `h:mm:ss` with optional fractions of a second, until the closing bracket
+fn parse_clock(text: &str) -> Option<Duration> {
+    let text = &text[..text.find(']')?];
+    let seconds = text.trim().split(':').try_fold(0.0, |total, part| part.parse::<f64>().ok().map(|part| total * 60.0 + part))?;
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sans(history: &History) -> Vec<&str> {
+        history.line().iter().filter_map(|ply| ply.san.as_deref()).collect()
+    }
+
+    #[test]
+    fn castling_can_be_written_with_zeros() {
+        let registry = PieceRegistry::standard();
+        let history = import("1.e4 e5 2.Nf3 Nf6 3.Bc4 Bc5 4.0-0 4...0-0 *", &registry).unwrap();
+        assert_eq!(sans(&history)[6..], ["O-O", "O-O"]);
+    }
+
+    #[test]
+    fn set_up_games_keep_their_position_and_move_numbers() {
+        let registry = PieceRegistry::standard();
+        let fen = "r3k3/8/8/8/3pP3/8/8/4K2R b Kq e3 0 23";
+        let history = import(&format!("[FEN \"{fen}\"]\n\n23... dxe3 24. O-O *"), &registry).unwrap();
+        assert_eq!(sans(&history), ["dxe3", "O-O"]);
+
+        let pgn = export(&history, &registry, "*");
+        assert!(pgn.contains(&format!("[SetUp \"1\"]\n[FEN \"{fen}\"]\n")));
+        assert!(pgn.ends_with("\n23... dxe3 24. O-O *\n"));
+    }
+
+    #[test]
+    fn standard_games_need_no_set_up() {
+        let registry = PieceRegistry::standard();
+        let history = import("1. e4 e5 2. Nf3 *", &registry).unwrap();
+        let pgn = export(&history, &registry, "*");
+        assert!(!pgn.contains("FEN"));
+        assert!(pgn.ends_with("\n1. e4 e5 2. Nf3 *\n"));
+    }
+}
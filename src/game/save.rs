@@ -0,0 +1,38 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::game::chess_board::Player;
+use crate::game::clock::Clocks;
+use crate::game::history::History;
+
+/// Everything needed to pick a game up where it was left, written as json.
+#[derive(Serialize, Deserialize)]
+pub struct SavedGame {
+    pub version: u32,
+    pub variant: Vec<String>, //the piece names of the config the game was played with, kinds are indices into it
+    pub history: History,
+    pub orientation: Player,
+    pub auto_flip: bool,
+    pub theme: String,
+    pub clocks: Option<Clocks>,
+}
+
+impl SavedGame {
+    /// bumped whenever a saved field changes meaning, older saves are then ignored
+    pub const VERSION: u32 = 2;
+
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// write next to the destination first, so being killed while saving doesn't lose the previous save
+    pub fn store(&self, path: &Path) -> std::io::Result<()> {
+        let temporary = path.with_extension("tmp");
+        let mut writer = BufWriter::new(File::create(&temporary)?);
+        serde_json::to_writer(&mut writer, self)?;
+        writer.flush()?;
+        std::fs::rename(temporary, path)
+    }
+}
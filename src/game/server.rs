@@ -0,0 +1,637 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use tungstenite::Message as Frame;
+use crate::game::chess_board::{ChessBoard, Player};
+use crate::game::clock::{Clocks, TimeControl};
+use crate::game::config::Config;
+use crate::game::fen;
+use crate::game::notation::{self, square_name};
+use crate::game::piece_registry::PieceRegistry;
+use crate::game::position::Position;
+
+pub type GameId = u64;
+type ClientId = u64;
+
+/// how often the clocks are checked for a flag fall, and the websockets for messages to send
+const TICK: Duration = Duration::from_millis(100);
+
+/// What clients ask, one json object per line over tcp or one text message over a websocket.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    List,
+    Create {
+        #[serde(default)]
+        time_control: Option<String>, //the name of a time control of the config, untimed when none
+        #[serde(default)]
+        fen: Option<String>,
+    },
+    Join {
+        game: GameId,
+        #[serde(default)]
+        player: Option<Player>, //the free side when none is asked
+    },
+    Watch { game: GameId },
+    Leave { game: GameId },
+    Move {
+        game: GameId,
+        #[serde(rename = "move")]
+        text: String, //in standard algebraic notation or with coordinates
+    },
+    Resign { game: GameId },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameSummary {
+    pub game: GameId,
+    pub players: Vec<Player>, //the seats taken
+    pub time_control: Option<String>,
+    pub moves: usize,
+    pub result: Option<String>,
+}
+
+/// What the server sends back, and what players and spectators of a game are told when it changes.
+/// Clocks are the seconds left to white and black, the moves of a state are played from its `fen`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    Games { games: Vec<GameSummary> },
+    Created { game: GameId },
+    Joined { game: GameId, player: Player },
+    State { game: GameId, fen: String, moves: Vec<String>, turn: Player, clocks: Option<[f64; 2]>, result: Option<String> },
+    Moved { game: GameId, san: String, from: String, to: String, turn: Player, clocks: Option<[f64; 2]> },
+    GameOver { game: GameId, result: String, reason: String },
+    Error { message: String },
+}
+
+fn index(player: Player) -> usize {
+    match player {
+        Player::White => 0,
+        Player::Black => 1,
+    }
+}
+
+struct Game {
+    id: GameId,
+    start: String, //the FEN of the position before the first move
+    board: ChessBoard,
+    moves: Vec<String>,
+    time_control: Option<String>,
+    clocks: Option<Clocks>,
+    last_update: Instant, //when the clocks were last ticked
+    seats: [Option<ClientId>; 2],
+    spectators: Vec<ClientId>,
+    result: Option<String>,
+}
+
+impl Game {
+    fn summary(&self) -> GameSummary {
+        GameSummary {
+            game: self.id,
+            players: [Player::White, Player::Black].into_iter().filter(|&player| self.seats[index(player)].is_some()).collect(),
+            time_control: self.time_control.clone(),
+            moves: self.moves.len(),
+            result: self.result.clone(),
+        }
+    }
+
+    fn clock_times(&self) -> Option<[f64; 2]> {
+        self.clocks.as_ref().map(|clocks| [Player::White, Player::Black].map(|player| clocks.remaining(player).as_secs_f64()))
+    }
+
+    fn state(&self) -> Event {
+        Event::State {
+            game: self.id,
+            fen: self.start.clone(),
+            moves: self.moves.clone(),
+            turn: self.board.turn(),
+            clocks: self.clock_times(),
+            result: self.result.clone(),
+        }
+    }
+
+    fn finish(&mut self, result: &str, reason: &str) -> Event {
+        self.result = Some(result.to_string());
+        Event::GameOver { game: self.id, result: result.to_string(), reason: reason.to_string() }
+    }
+
+    fn winner(player: Player) -> &'static str {
+        match player {
+            Player::White => "1-0",
+            Player::Black => "0-1",
+        }
+    }
+
+    /// run the clock of the side to move, the game is lost when it falls
+    fn update(&mut self, now: Instant) -> Option<Event> {
+        let elapsed = now.saturating_duration_since(self.last_update);
+        self.last_update = now;
+        if self.result.is_some() {
+            return None;
+        }
+        let clocks = self.clocks.as_mut()?;
+        clocks.tick(self.board.turn(), elapsed);
+        let flagged = clocks.flagged()?;
+        Some(self.finish(Self::winner(flagged.opponent()), "time"))
+    }
+
+    fn audience(&self) -> impl Iterator<Item = ClientId> + '_ {
+        self.seats.iter().flatten().chain(&self.spectators).copied()
+    }
+}
+
+/// Every game and every connected client, behind the lock shared by the connections.
+struct Lobby {
+    registry: PieceRegistry,
+    time_controls: Vec<TimeControl>,
+    games: HashMap<GameId, Game>,
+    next_game: GameId,
+    clients: HashMap<ClientId, Sender<Event>>,
+    next_client: ClientId,
+}
+
+impl Lobby {
+    fn connect(&mut self, sender: Sender<Event>) -> ClientId {
+        self.next_client += 1;
+        self.clients.insert(self.next_client, sender);
+        self.next_client
+    }
+
+    /// the seats of the client are freed, it can take them again by joining with the same side
+    fn disconnect(&mut self, client: ClientId) {
+        self.clients.remove(&client);
+        for game in self.games.values_mut() {
+            Self::leave(game, client);
+        }
+    }
+
+    fn leave(game: &mut Game, client: ClientId) {
+        for seat in &mut game.seats {
+            if *seat == Some(client) {
+                *seat = None;
+            }
+        }
+        game.spectators.retain(|&spectator| spectator != client);
+    }
+
+    fn send(&self, client: ClientId, event: Event) {
+        if let Some(sender) = self.clients.get(&client) {
+            let _ = sender.send(event);
+        }
+    }
+
+    fn broadcast(&self, game: GameId, event: &Event) {
+        for client in self.games.get(&game).into_iter().flat_map(Game::audience) {
+            self.send(client, event.clone());
+        }
+    }
+
+    fn game(&mut self, game: GameId) -> Result<&mut Game, String> {
+        self.games.get_mut(&game).ok_or_else(|| format!("there is no game {game}"))
+    }
+
+    fn handle(&mut self, client: ClientId, request: Request) {
+        if let Err(message) = self.answer(client, request) {
+            self.send(client, Event::Error { message });
+        }
+    }
+
+    fn answer(&mut self, client: ClientId, request: Request) -> Result<(), String> {
+        match request {
+            Request::List => {
+                let mut games: Vec<_> = self.games.values().map(Game::summary).collect();
+                games.sort_by_key(|summary| summary.game);
+                self.send(client, Event::Games { games });
+            }
+            Request::Create { time_control, fen } => {
+                let clocks = match &time_control {
+                    Some(name) => {
+                        let control = self.time_controls.iter().find(|control| &control.name == name);
+                        Some(Clocks::new(control.ok_or_else(|| format!("there is no time control {name}"))?.clone()))
+                    }
+                    None => None,
+                };
+                let (start, board) = match fen {
+                    Some(fen) => {
+                        let board = fen::parse(&fen, &self.registry).map_err(|error| error.to_string())?;
+                        (fen.trim().to_string(), board)
+                    }
+                    None => (fen::STARTING_POSITION.to_string(), ChessBoard::new()),
+                };
+                self.next_game += 1;
+                let id = self.next_game;
+                self.games.insert(id, Game {
+                    id,
+                    start,
+                    board,
+                    moves: Vec::new(),
+                    time_control,
+                    clocks,
+                    last_update: Instant::now(),
+                    seats: [None; 2],
+                    spectators: Vec::new(),
+                    result: None,
+                });
+                self.send(client, Event::Created { game: id });
+            }
+            Request::Join { game: id, player } => {
+                let game = self.game(id)?;
+                let player = player
+                    .or_else(|| [Player::White, Player::Black].into_iter().find(|&player| game.seats[index(player)].is_none()))
+                    .ok_or("both seats are taken")?;
+                if game.seats[index(player.opponent())] == Some(client) {
+                    return Err(format!("you already play {:?} in this game", player.opponent()));
+                }
+                let seat = &mut game.seats[index(player)];
+                if seat.is_some_and(|seated| seated != client) {
+                    return Err(format!("{player:?} is already played by someone else"));
+                }
+                *seat = Some(client);
+                game.spectators.retain(|&spectator| spectator != client);
+                let state = game.state();
+                self.send(client, Event::Joined { game: id, player });
+                self.send(client, state);
+            }
+            Request::Watch { game: id } => {
+                let game = self.game(id)?;
+                if !game.spectators.contains(&client) {
+                    game.spectators.push(client);
+                }
+                let state = game.state();
+                self.send(client, state);
+            }
+            Request::Leave { game: id } => Self::leave(self.game(id)?, client),
+            Request::Move { game: id, text } => self.play(client, id, &text)?,
+            Request::Resign { game: id } => {
+                let game = self.game(id)?;
+                let player = [Player::White, Player::Black].into_iter()
+                    .find(|&player| game.seats[index(player)] == Some(client))
+                    .ok_or("you don't play in this game")?;
+                if game.result.is_some() {
+                    return Err("the game is over".to_string());
+                }
+                let over = game.finish(Game::winner(player.opponent()), "resignation");
+                self.broadcast(id, &over);
+            }
+        }
+        Ok(())
+    }
+
+    /// only the client seated on the side to move can play, while its clock still runs
+    fn play(&mut self, client: ClientId, id: GameId, text: &str) -> Result<(), String> {
+        let registry = &self.registry;
+        let game = self.games.get_mut(&id).ok_or_else(|| format!("there is no game {id}"))?;
+        let flagged = game.update(Instant::now());
+        if let Some(over) = flagged {
+            self.broadcast(id, &over);
+            return Err("the game is over".to_string());
+        }
+        if game.result.is_some() {
+            return Err("the game is over".to_string());
+        }
+        let turn = game.board.turn();
+        if game.seats[index(turn)] != Some(client) {
+            return Err(if game.seats.contains(&Some(client)) { "it's not your turn" } else { "you don't play in this game" }.to_string());
+        }
+
+        let played = notation::parse_move(&game.board, registry, text).map_err(|error| format!("{error}: {text}"))?;
+        let san = notation::san(&game.board, registry, played);
+        game.board.move_piece(played);
+        if let Some(clocks) = &mut game.clocks {
+            clocks.moved(turn);
+        }
+        game.moves.push(san.clone());
+        let moved = Event::Moved {
+            game: id,
+            san,
+            from: square_name(played.from.0, played.from.1),
+            to: square_name(played.to.0, played.to.1),
+            turn: game.board.turn(),
+            clocks: game.clock_times(),
+        };
+
+        let over = game.board.result(registry).map(|result| game.finish(result.score(), result.reason()));
+        self.broadcast(id, &moved);
+        if let Some(over) = over {
+            self.broadcast(id, &over);
+        }
+        Ok(())
+    }
+
+    /// end the games whose clock fell since the last tick
+    fn tick(&mut self, now: Instant) {
+        let over: Vec<_> = self.games.values_mut().filter_map(|game| Some((game.id, game.update(now)?))).collect();
+        for (id, event) in over {
+            self.broadcast(id, &event);
+        }
+    }
+}
+
+fn lock(lobby: &Mutex<Lobby>) -> MutexGuard<'_, Lobby> {
+    lobby.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Hosts any number of games for clients connecting over tcp, with one json object per line,
+/// or over a websocket on the same port, with one json object per text message.
+/// Players take a seat in a game and spectators watch it, both are told about every move.
+pub struct Server {
+    listener: TcpListener,
+    lobby: Arc<Mutex<Lobby>>,
+}
+
+impl Server {
+    /// the pieces and time controls come from the config in `config_path`
+    pub fn bind(address: impl ToSocketAddrs, config_path: &Path) -> io::Result<Self> {
+        let config = Config::load(config_path)?;
+        let lobby = Lobby {
+            registry: PieceRegistry::from_config(&config.pieces),
+            time_controls: config.time_controls,
+            games: HashMap::new(),
+            next_game: 0,
+            clients: HashMap::new(),
+            next_client: 0,
+        };
+        Ok(Self {
+            listener: TcpListener::bind(address)?,
+            lobby: Arc::new(Mutex::new(lobby)),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// serve clients until the listener fails, each connection gets its own thread
+    pub fn run(self) -> io::Result<()> {
+        let lobby = self.lobby.clone();
+        thread::spawn(move || loop {
+            thread::sleep(TICK);
+            lock(&lobby).tick(Instant::now());
+        });
+
+        for stream in self.listener.incoming() {
+            let stream = stream?;
+            let lobby = self.lobby.clone();
+            thread::spawn(move || {
+                let peer = stream.peer_addr().map_or("a client".to_string(), |address| address.to_string());
+                if let Err(error) = serve(stream, &lobby) {
+                    log::info!("{peer} left: {error}");
+                }
+            });
+        }
+        Ok(())
+    }
+}
+
+fn serve(stream: TcpStream, lobby: &Mutex<Lobby>) -> io::Result<()> {
+    // websocket clients start with an http upgrade request
+    let mut start = [0; 4];
+    let websocket = stream.peek(&mut start)? == start.len() && &start == b"GET ";
+
+    let (sender, events) = mpsc::channel();
+    let client = lock(lobby).connect(sender);
+    let served = if websocket {
+        serve_websocket(stream, client, lobby, events)
+    } else {
+        serve_lines(stream, client, lobby, events)
+    };
+    lock(lobby).disconnect(client);
+    served
+}
+
+fn parse_request(text: &str) -> Result<Request, Event> {
+    serde_json::from_str(text).map_err(|error| Event::Error { message: format!("unreadable request: {error}") })
+}
+
+fn serve_lines(stream: TcpStream, client: ClientId, lobby: &Mutex<Lobby>, events: Receiver<Event>) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    // stops once the client is disconnected from the lobby, which drops the sender
+    thread::spawn(move || {
+        for event in events {
+            let line = serde_json::to_string(&event).expect("events can always be serialized");
+            if writeln!(writer, "{line}").is_err() {
+                break;
+            }
+        }
+    });
+
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_request(&line) {
+            Ok(request) => lock(lobby).handle(client, request),
+            Err(error) => lock(lobby).send(client, error),
+        }
+    }
+    Ok(())
+}
+
+/// a websocket can't be read and written from two threads, so reads time out to send the events in between
+fn serve_websocket(stream: TcpStream, client: ClientId, lobby: &Mutex<Lobby>, events: Receiver<Event>) -> io::Result<()> {
+    let mut socket = tungstenite::accept(stream).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error.to_string()))?;
+    socket.get_mut().set_read_timeout(Some(TICK))?;
+    let closed = |error: tungstenite::Error| io::Error::new(io::ErrorKind::ConnectionAborted, error.to_string());
+    loop {
+        for event in events.try_iter() {
+            let text = serde_json::to_string(&event).expect("events can always be serialized");
+            socket.send(Frame::text(text)).map_err(closed)?;
+        }
+        match socket.read() {
+            Ok(Frame::Text(text)) => match parse_request(&text) {
+                Ok(request) => lock(lobby).handle(client, request),
+                Err(error) => lock(lobby).send(client, error),
+            },
+            Ok(Frame::Close(_)) => return Ok(()),
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(error)) if matches!(error.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {}
+            Err(tungstenite::Error::ConnectionClosed) => return Ok(()),
+            Err(error) => return Err(closed(error)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::clock::Period;
+
+    fn lobby() -> Lobby {
+        let path = Path::new("config");
+        let config = Config::load(path).unwrap();
+        let second = Duration::from_secs(1);
+        Lobby {
+            registry: PieceRegistry::from_config(&config.pieces),
+            time_controls: vec![TimeControl {
+                name: "One second".to_string(),
+                periods: vec![Period { moves: None, time: second, increment: Duration::ZERO, delay: Duration::ZERO }],
+            }],
+            games: HashMap::new(),
+            next_game: 0,
+            clients: HashMap::new(),
+            next_client: 0,
+        }
+    }
+
+    type Client = (ClientId, Receiver<Event>);
+
+    fn client(lobby: &mut Lobby) -> Client {
+        let (sender, events) = mpsc::channel();
+        (lobby.connect(sender), events)
+    }
+
+    fn play(lobby: &mut Lobby, client: ClientId, game: GameId, text: &str) {
+        lobby.handle(client, Request::Move { game, text: text.to_string() });
+    }
+
+    /// a game with both seats taken
+    fn seated(lobby: &mut Lobby, time_control: Option<&str>) -> (GameId, Client, Client) {
+        let white = client(lobby);
+        let black = client(lobby);
+        lobby.handle(white.0, Request::Create { time_control: time_control.map(str::to_string), fen: None });
+        let game = lobby.next_game;
+        lobby.handle(white.0, Request::Join { game, player: Some(Player::White) });
+        lobby.handle(black.0, Request::Join { game, player: None });
+        for (_, events) in [&white, &black] {
+            events.try_iter().for_each(drop);
+        }
+        (game, white, black)
+    }
+
+    fn send_line(stream: &mut TcpStream, request: &Request) {
+        writeln!(stream, "{}", serde_json::to_string(request).unwrap()).unwrap();
+    }
+
+    fn read_line(reader: &mut BufReader<TcpStream>) -> Event {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    #[test]
+    fn only_the_side_to_move_plays() {
+        let mut lobby = lobby();
+        let (game, (white, white_events), (black, black_events)) = seated(&mut lobby, None);
+        let (spectator, spectator_events) = client(&mut lobby);
+        lobby.handle(spectator, Request::Watch { game });
+        assert!(matches!(spectator_events.try_recv(), Ok(Event::State { moves, .. }) if moves.is_empty()));
+
+        play(&mut lobby, black, game, "e5");
+        assert!(matches!(black_events.try_recv(), Ok(Event::Error { message }) if message == "it's not your turn"));
+        play(&mut lobby, spectator, game, "e4");
+        assert!(matches!(spectator_events.try_recv(), Ok(Event::Error { message }) if message == "you don't play in this game"));
+        play(&mut lobby, white, game, "e5");
+        assert!(matches!(white_events.try_recv(), Ok(Event::Error { .. })));
+
+        play(&mut lobby, white, game, "e4");
+        for events in [&white_events, &black_events, &spectator_events] {
+            assert!(matches!(events.try_recv(), Ok(Event::Moved { san, turn: Player::Black, .. }) if san == "e4"));
+        }
+    }
+
+    #[test]
+    fn one_client_takes_one_seat() {
+        let mut lobby = lobby();
+        let (client, events) = client(&mut lobby);
+        lobby.handle(client, Request::Create { time_control: None, fen: None });
+        let game = lobby.next_game;
+        lobby.handle(client, Request::Join { game, player: Some(Player::White) });
+        events.try_iter().for_each(drop);
+
+        lobby.handle(client, Request::Join { game, player: Some(Player::Black) });
+        assert!(matches!(events.try_recv(), Ok(Event::Error { message }) if message == "you already play White in this game"));
+        lobby.handle(client, Request::Join { game, player: None });
+        assert!(matches!(events.try_recv(), Ok(Event::Error { .. })));
+        assert_eq!(lobby.games[&game].seats, [Some(client), None]);
+    }
+
+    #[test]
+    fn the_state_tells_the_starting_position() {
+        let mut lobby = lobby();
+        let (client, events) = client(&mut lobby);
+        let fen = "4k3/8/8/8/8/8/8/4K2R b K - 0 12";
+        lobby.handle(client, Request::Create { time_control: None, fen: Some(fen.to_string()) });
+        lobby.handle(client, Request::Watch { game: lobby.next_game });
+        let state = events.try_iter().last();
+        assert!(matches!(state, Some(Event::State { fen: start, turn: Player::Black, .. }) if start == fen));
+    }
+
+    #[test]
+    fn mate_ends_the_game() {
+        let mut lobby = lobby();
+        let (game, (white, _), (black, black_events)) = seated(&mut lobby, None);
+        for (client, text) in [(white, "f3"), (black, "e5"), (white, "g4"), (black, "Qh4")] {
+            play(&mut lobby, client, game, text);
+        }
+        let events: Vec<_> = black_events.try_iter().collect();
+        assert_eq!(events.last(), Some(&Event::GameOver { game, result: "0-1".to_string(), reason: "checkmate".to_string() }));
+        play(&mut lobby, white, game, "a3");
+        assert_eq!(lobby.games[&game].moves.len(), 4);
+    }
+
+    #[test]
+    fn the_clock_falls() {
+        let mut lobby = lobby();
+        let (game, (white, white_events), (black, black_events)) = seated(&mut lobby, Some("One second"));
+        play(&mut lobby, white, game, "e4");
+        assert!(matches!(white_events.try_recv(), Ok(Event::Moved { clocks: Some([white_time, black_time]), .. }) if white_time == 1.0 && black_time == 1.0));
+
+        lobby.tick(Instant::now() + Duration::from_secs(2));
+        let over = Event::GameOver { game, result: "1-0".to_string(), reason: "time".to_string() };
+        assert_eq!(white_events.try_iter().last(), Some(over));
+        play(&mut lobby, black, game, "e5");
+        assert!(matches!(black_events.try_iter().last(), Some(Event::Error { message }) if message == "the game is over"));
+    }
+
+    #[test]
+    fn a_missing_config_is_an_error() {
+        let error = Server::bind("127.0.0.1:0", Path::new("no_such_config")).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn tcp_and_websocket_clients_share_games() {
+        let server = Server::bind("127.0.0.1:0", Path::new("config")).unwrap();
+        let address = server.local_addr().unwrap();
+        thread::spawn(move || server.run());
+
+        let mut tcp = TcpStream::connect(address).unwrap();
+        let mut lines = BufReader::new(tcp.try_clone().unwrap());
+        send_line(&mut tcp, &Request::Create { time_control: None, fen: None });
+        let Event::Created { game } = read_line(&mut lines) else {
+            panic!("the game wasn't created");
+        };
+        send_line(&mut tcp, &Request::Join { game, player: Some(Player::White) });
+        assert_eq!(read_line(&mut lines), Event::Joined { game, player: Player::White });
+        assert!(matches!(read_line(&mut lines), Event::State { .. }));
+
+        let (mut websocket, _) = tungstenite::connect(format!("ws://{address}/")).unwrap();
+        let mut ask = |request: &Request| {
+            websocket.send(Frame::text(serde_json::to_string(request).unwrap())).unwrap();
+            let Frame::Text(text) = websocket.read().unwrap() else {
+                panic!("expected a text message");
+            };
+            serde_json::from_str::<Event>(&text).unwrap()
+        };
+        assert_eq!(ask(&Request::Join { game, player: None }), Event::Joined { game, player: Player::Black });
+
+        send_line(&mut tcp, &Request::Move { game, text: "Nf3".to_string() });
+        assert!(matches!(read_line(&mut lines), Event::Moved { san, .. } if san == "Nf3"));
+        let Frame::Text(text) = websocket.read().unwrap() else {
+            panic!("expected a text message");
+        };
+        assert!(matches!(serde_json::from_str(&text).unwrap(), Event::State { .. }));
+        let Frame::Text(text) = websocket.read().unwrap() else {
+            panic!("expected a text message");
+        };
+        assert!(matches!(serde_json::from_str(&text).unwrap(), Event::Moved { from, to, .. } if from == "g1" && to == "f3"));
+    }
+}
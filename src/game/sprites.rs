@@ -0,0 +1,75 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use vello::Scene;
+use vello_svg::render;
+use crate::game::chess_board::Player;
+use crate::game::config::Config;
+use crate::game::piece_registry::PieceKind;
+
+/// A piece image, rendered once for the screen and kept as svg for documents.
+struct Sprite {
+    svg: String,
+    scene: Scene,
+}
+
+impl Sprite {
+    fn new(svg: String) -> io::Result<Self> {
+        let scene = render(&svg).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error.to_string()))?;
+        Ok(Self { svg, scene })
+    }
+}
+
+/// The images of the pieces, in the order of their kinds in the registry.
+pub struct PieceSprites {
+    sprites: Vec<[Sprite; 2]>, //black then white
+}
+
+impl PieceSprites {
+    /// sprite paths in the config are relative to `path`
+    pub fn load_from_config(config: &Config, path: &Path) -> io::Result<Self> {
+        let read = |file: &Path| {
+            let file = path.join(file);
+            let in_file = |error: io::Error| io::Error::new(error.kind(), format!("{}: {error}", file.display()));
+            Sprite::new(fs::read_to_string(&file).map_err(in_file)?).map_err(in_file)
+        };
+        Ok(Self {
+            sprites: config.pieces.iter()
+                .map(|piece| Ok([read(&piece.black_sprite)?, read(&piece.white_sprite)?]))
+                .collect::<io::Result<_>>()?,
+        })
+    }
+
+    /// the images of `PieceRegistry::standard`, bundled with the app
+    #[cfg(target_os = "android")]
+    pub fn bundled() -> Self {
+        let sprite = |svg: &str| Sprite::new(svg.to_string()).expect("the bundled sprites are valid svg");
+        Self {
+            sprites: vec![
+                [sprite(include_str!("../../assets/black_pawn.svg")), sprite(include_str!("../../assets/white_pawn.svg"))],
+                [sprite(include_str!("../../assets/black_rook.svg")), sprite(include_str!("../../assets/white_rook.svg"))],
+                [sprite(include_str!("../../assets/black_knight.svg")), sprite(include_str!("../../assets/white_knight.svg"))],
+                [sprite(include_str!("../../assets/black_bishop.svg")), sprite(include_str!("../../assets/white_bishop.svg"))],
+                [sprite(include_str!("../../assets/black_queen.svg")), sprite(include_str!("../../assets/white_queen.svg"))],
+                [sprite(include_str!("../../assets/black_king.svg")), sprite(include_str!("../../assets/white_king.svg"))],
+            ],
+        }
+    }
+
+    fn sprite(&self, piece: PieceKind, player: Player) -> &Sprite {
+        let [black, white] = &self.sprites[piece.index()];
+        match player {
+            Player::Black => black,
+            Player::White => white,
+        }
+    }
+
+    pub fn get_sprite(&self, piece: PieceKind, player: Player) -> &Scene {
+        &self.sprite(piece, player).scene
+    }
+
+    /// the svg file the sprite was made from
+    pub fn get_svg(&self, piece: PieceKind, player: Player) -> &str {
+        &self.sprite(piece, player).svg
+    }
+}
@@ -0,0 +1,71 @@
+use std::sync::Arc;
+use vello::kurbo::Affine;
+use vello::peniko::{Blob, Color, Fill, Font};
+use vello::skrifa::instance::{LocationRef, Size};
+use vello::skrifa::raw::FileRef;
+use vello::skrifa::{FontRef, MetadataProvider};
+use vello::{Glyph, Scene};
+
+//bundled so the same font is available on every platform, android included
+const FONT_DATA: &[u8] = include_bytes!("../../assets/fonts/Roboto-Regular.ttf");
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Align {
+    Start,
+    End,
+}
+
+/// Lays out single lines of text with the bundled font.
+pub struct TextRenderer {
+    font: Font,
+}
+
+impl TextRenderer {
+    pub fn new() -> Self {
+        Self {
+            font: Font::new(Blob::new(Arc::new(FONT_DATA)), 0),
+        }
+    }
+
+    fn font_ref(&self) -> FontRef<'_> {
+        match FileRef::new(self.font.data.as_ref()).expect("bundled font is valid") {
+            FileRef::Font(font) => font,
+            FileRef::Collection(collection) => collection.get(self.font.index).expect("bundled font is valid"),
+        }
+    }
+
+    fn layout(&self, text: &str, size: f32) -> (Vec<Glyph>, f32) {
+        let font = self.font_ref();
+        let charmap = font.charmap();
+        let metrics = font.glyph_metrics(Size::new(size), LocationRef::default());
+
+        let mut pen_x = 0.0;
+        let glyphs = text.chars().map(|c| {
+            let id = charmap.map(c).unwrap_or_default();
+            let x = pen_x;
+            pen_x += metrics.advance_width(id).unwrap_or_default();
+            Glyph { id: id.to_u32(), x, y: 0.0 }
+        }).collect();
+        (glyphs, pen_x)
+    }
+
+    /// height of the capital letters, useful to center a line vertically
+    pub fn cap_height(&self, size: f32) -> f64 {
+        let metrics = self.font_ref().metrics(Size::new(size), LocationRef::default());
+        metrics.cap_height.unwrap_or(metrics.ascent) as f64
+    }
+
+    /// draw a line of text, `transform` places the baseline at the origin
+    pub fn draw(&self, scene: &mut Scene, text: &str, size: f32, align: Align, color: Color, transform: Affine) {
+        let (glyphs, width) = self.layout(text, size);
+        let offset = match align {
+            Align::Start => 0.0,
+            Align::End => -width as f64,
+        };
+        scene.draw_glyphs(&self.font)
+            .font_size(size)
+            .transform(transform * Affine::translate((offset, 0.0)))
+            .brush(color)
+            .draw(Fill::NonZero, glyphs.into_iter());
+    }
+}
@@ -0,0 +1,76 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use vello::peniko::Color;
+
+/// Colors used to draw the board, every color can carry an alpha channel.
+/// In the config they are written as css colors: `#rrggbb`, `#rrggbbaa` or a svg color name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Theme {
+    pub name: String,
+    #[serde(with = "css_color")]
+    pub light_square: Color,
+    #[serde(with = "css_color")]
+    pub dark_square: Color,
+    #[serde(with = "css_color")]
+    pub selection: Color,
+    #[serde(with = "css_color")]
+    pub move_hint: Color,
+    #[serde(with = "css_color")]
+    pub last_move: Color,
+    #[serde(with = "css_color")]
+    pub check: Color,
+}
+
+impl Theme {
+    /// the themes of the bundled config, used when the config doesn't provide any
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self {
+                name: "Ocean".to_string(),
+                light_square: Color::rgb8(230, 230, 204),
+                dark_square: Color::rgb8(51, 77, 128),
+                selection: Color::RED,
+                move_hint: Color::rgba8(0, 255, 0, 150),
+                last_move: Color::rgba8(255, 255, 0, 80),
+                check: Color::rgba8(255, 0, 0, 128),
+            },
+            Self {
+                name: "Wood".to_string(),
+                light_square: Color::rgb8(240, 217, 181),
+                dark_square: Color::rgb8(181, 136, 99),
+                selection: Color::rgb8(20, 85, 30),
+                move_hint: Color::rgba8(20, 85, 30, 128),
+                last_move: Color::rgba8(155, 199, 0, 105),
+                check: Color::rgba8(255, 0, 0, 128),
+            },
+            Self {
+                name: "Forest".to_string(),
+                light_square: Color::rgb8(238, 238, 210),
+                dark_square: Color::rgb8(118, 150, 86),
+                selection: Color::rgb8(30, 58, 138),
+                move_hint: Color::rgba8(30, 58, 138, 112),
+                last_move: Color::rgba8(246, 246, 105, 128),
+                check: Color::rgba8(224, 40, 40, 128),
+            },
+        ]
+    }
+
+    /// the themes of the config, or the defaults when its list is empty
+    pub fn deserialize_list<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Self>, D::Error> {
+        let themes = Vec::deserialize(deserializer)?;
+        Ok(if themes.is_empty() { Self::defaults() } else { themes })
+    }
+}
+
+mod css_color {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        let Color { r, g, b, a } = color;
+        serializer.serialize_str(&format!("#{r:02x}{g:02x}{b:02x}{a:02x}"))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        let string = String::deserialize(deserializer)?;
+        Color::parse(&string).ok_or_else(|| serde::de::Error::custom(format!("invalid color `{string}`")))
+    }
+}
@@ -0,0 +1,200 @@
+use std::fmt::{Display, Formatter};
+use std::num::NonZeroUsize;
+use std::time::Duration;
+use vello::peniko::Color;
+use vello::wgpu::{self, Device, Queue};
+use vello::{AaConfig, AaSupport, RenderParams, Renderer, RendererOptions, Scene};
+
+/// Why an image couldn't be rendered without a window.
+#[derive(Debug)]
+pub enum HeadlessError {
+    NoAdapter,
+    Device(wgpu::RequestDeviceError),
+    Render(vello::Error),
+    Readback(wgpu::BufferAsyncError),
+    Png(png::EncodingError),
+    Gif(gif::EncodingError),
+}
+
+impl Display for HeadlessError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HeadlessError::NoAdapter => write!(f, "no graphics adapter found, not even a software one (install mesa's lavapipe or llvmpipe)"),
+            HeadlessError::Device(error) => write!(f, "couldn't open the graphics device: {error}"),
+            HeadlessError::Render(error) => write!(f, "couldn't render the scene: {error}"),
+            HeadlessError::Readback(error) => write!(f, "couldn't read the rendered image back: {error}"),
+            HeadlessError::Png(error) => write!(f, "couldn't encode the png: {error}"),
+            HeadlessError::Gif(error) => write!(f, "couldn't encode the gif: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for HeadlessError {}
+
+/// Scenes of the same size shown one after the other, each for its own duration.
+pub struct Animation {
+    pub width: u32,
+    pub height: u32,
+    pub background: Color,
+    pub frames: Vec<(Scene, Duration)>,
+}
+
+/// Renders vello scenes into memory instead of a window surface.
+/// Everything up to fine rasterization runs on the CPU, so a software adapter such as
+/// lavapipe or llvmpipe is enough and the images can be made on a machine without a GPU.
+pub struct HeadlessRenderer {
+    device: Device,
+    queue: Queue,
+    renderer: Renderer,
+}
+
+impl HeadlessRenderer {
+    pub fn new() -> Result<Self, HeadlessError> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: wgpu::util::backend_bits_from_env().unwrap_or(wgpu::Backends::all()),
+            ..Default::default()
+        });
+        // a real adapter is preferred, the software fallback is only asked for when there is none
+        let adapter = [false, true].into_iter()
+            .find_map(|force_fallback_adapter| {
+                pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: wgpu::PowerPreference::default(),
+                    force_fallback_adapter,
+                    compatible_surface: None,
+                }))
+            })
+            .ok_or(HeadlessError::NoAdapter)?;
+        log::info!("rendering with {:?}", adapter.get_info());
+
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: None,
+                required_features: adapter.features() & wgpu::Features::CLEAR_TEXTURE,
+                required_limits: wgpu::Limits::default(),
+                memory_hints: Default::default(),
+            },
+            None,
+        )).map_err(HeadlessError::Device)?;
+
+        let renderer = Renderer::new(&device, RendererOptions {
+            surface_format: None,
+            use_cpu: true,
+            antialiasing_support: AaSupport::area_only(),
+            num_init_threads: NonZeroUsize::new(1),
+        }).map_err(HeadlessError::Render)?;
+
+        Ok(Self { device, queue, renderer })
+    }
+
+    /// the scene rendered over `background`, as rows of RGBA pixels
+    pub fn render(&mut self, scene: &Scene, width: u32, height: u32, background: Color) -> Result<Vec<u8>, HeadlessError> {
+        let size = wgpu::Extent3d { width, height, depth_or_array_layers: 1 };
+        let target = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("headless target"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = target.create_view(&wgpu::TextureViewDescriptor::default());
+        let params = RenderParams {
+            base_color: background,
+            width,
+            height,
+            antialiasing_method: AaConfig::Area,
+        };
+        self.renderer.render_to_texture(&self.device, &self.queue, scene, &view, &params)
+            .map_err(HeadlessError::Render)?;
+
+        // rows of a texture copy must be aligned, the padding is removed while reading
+        let row_size = width as usize * 4;
+        let padded_row_size = row_size.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as usize);
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("headless readback"),
+            size: (padded_row_size * height as usize) as u64,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        encoder.copy_texture_to_buffer(
+            target.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row_size as u32),
+                    rows_per_image: None,
+                },
+            },
+            size,
+        );
+        self.queue.submit([encoder.finish()]);
+
+        let slice = buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver.recv().expect("the buffer mapping always answers")
+            .map_err(HeadlessError::Readback)?;
+
+        let data = slice.get_mapped_range();
+        let pixels = data.chunks(padded_row_size)
+            .flat_map(|row| &row[..row_size])
+            .copied()
+            .collect();
+        Ok(pixels)
+    }
+}
+
+impl HeadlessRenderer {
+    /// an animated png looping forever, keeping the full colors of every frame
+    pub fn render_apng(&mut self, animation: &Animation) -> Result<Vec<u8>, HeadlessError> {
+        let mut apng = Vec::new();
+        let mut encoder = png::Encoder::new(&mut apng, animation.width, animation.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_animated(animation.frames.len() as u32, 0).map_err(HeadlessError::Png)?;
+        let mut writer = encoder.write_header().map_err(HeadlessError::Png)?;
+        for (scene, duration) in &animation.frames {
+            let pixels = self.render(scene, animation.width, animation.height, animation.background)?;
+            let milliseconds = duration.as_millis().min(u16::MAX as u128) as u16;
+            writer.set_frame_delay(milliseconds, 1000).map_err(HeadlessError::Png)?;
+            writer.write_image_data(&pixels).map_err(HeadlessError::Png)?;
+        }
+        writer.finish().map_err(HeadlessError::Png)?;
+        Ok(apng)
+    }
+
+    /// a gif looping forever, each frame reduced to its own 256 colors palette
+    pub fn render_gif(&mut self, animation: &Animation) -> Result<Vec<u8>, HeadlessError> {
+        let (width, height) = (animation.width as u16, animation.height as u16);
+        let mut gif = Vec::new();
+        let mut encoder = gif::Encoder::new(&mut gif, width, height, &[]).map_err(HeadlessError::Gif)?;
+        encoder.set_repeat(gif::Repeat::Infinite).map_err(HeadlessError::Gif)?;
+        for (scene, duration) in &animation.frames {
+            let mut pixels = self.render(scene, animation.width, animation.height, animation.background)?;
+            let mut frame = gif::Frame::from_rgba_speed(width, height, &mut pixels, 10);
+            frame.delay = (duration.as_millis() / 10).min(u16::MAX as u128) as u16; //in hundredths of a second
+            encoder.write_frame(&frame).map_err(HeadlessError::Gif)?;
+        }
+        drop(encoder);
+        Ok(gif)
+    }
+}
+
+/// encode RGBA pixels as a png file
+pub fn encode_png(pixels: &[u8], width: u32, height: u32) -> Result<Vec<u8>, HeadlessError> {
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(HeadlessError::Png)?;
+    writer.write_image_data(pixels).map_err(HeadlessError::Png)?;
+    writer.finish().map_err(HeadlessError::Png)?;
+    Ok(png)
+}
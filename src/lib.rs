@@ -1,8 +1,13 @@
 extern crate log;
+#[cfg(feature = "graphics")]
 pub mod app;
 pub mod game;
+#[cfg(feature = "graphics")]
+pub mod headless;
+/// the board, the pieces and the notation, the same as the `chess_rules` crate
+pub use chess_rules as rules;
 
-#[cfg(target_os = "android")]
+#[cfg(all(target_os = "android", feature = "graphics"))]
 #[export_name = "android_main"]
 pub fn main(android_app: winit::platform::android::activity::AndroidApp) {
 
@@ -25,7 +30,11 @@ pub fn main(android_app: winit::platform::android::activity::AndroidApp) {
     use winit::platform::android::EventLoopBuilderExtAndroid;
 
     // Setup a bunch of state:
-    let game_state = ChessGame::new();
+    let mut game_state = ChessGame::new();
+    match android_app.internal_data_path() {
+        Some(path) => game_state.resume_from(path.join("saved_game.json")),
+        None => log::warn!("no internal storage, the game won't be saved"),
+    }
     let mut app = SimpleVelloApp::new(game_state);
 
     // Create and run a winit event loop
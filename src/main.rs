@@ -1,11 +1,19 @@
+use std::path::PathBuf;
 use winit::event_loop::EventLoop;
 use chess_game::app::SimpleVelloApp;
-use chess_game::game::ChessGame;
+use chess_game::game::{ChessGame, Player, DEFAULT_PORT};
+
+const USAGE: &str = "usage: entrypoint [--host [PORT] [--black] | --join ADDRESS:PORT]";
 
 #[cfg(not(target_os = "android"))]
 fn main() {
     // Setup a bunch of state:
-    let game_state = ChessGame::new();
+    let mut game_state = ChessGame::new();
+    if let Err(error) = start_network_game(&mut game_state) {
+        eprintln!("{error}\n{USAGE}");
+        std::process::exit(1);
+    }
+    game_state.resume_from(PathBuf::from("saved_game.json"));
     let mut app = SimpleVelloApp::new(game_state);
 
     // Create and run a winit event loop
@@ -13,4 +21,57 @@ fn main() {
     event_loop
         .run_app(&mut app)
         .expect("Couldn't run event loop");
-}
\ No newline at end of file
+}
+
+/// What the command line asks for, the flags can come in any order.
+#[derive(Default)]
+struct Args {
+    host: Option<u16>,
+    join: Option<String>,
+    black: bool,
+}
+
+impl Args {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter().peekable();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--host" => {
+                    let port = args.next_if(|arg| !arg.starts_with("--"));
+                    parsed.host = Some(match port {
+                        Some(port) => port.parse().map_err(|error| format!("bad port: {error}"))?,
+                        None => DEFAULT_PORT,
+                    });
+                }
+                "--join" => parsed.join = Some(args.next().ok_or("--join needs an address")?),
+                "--black" => parsed.black = true,
+                _ => return Err(format!("unknown argument: {arg}")),
+            }
+        }
+        if parsed.host.is_some() && parsed.join.is_some() {
+            return Err("--host and --join can't be used together".to_string());
+        }
+        if parsed.black && parsed.host.is_none() && parsed.join.is_none() {
+            return Err("--black is only for a network game".to_string());
+        }
+        Ok(parsed)
+    }
+}
+
+/// `--host` waits for another instance to join, playing white unless `--black` is given
+fn start_network_game(game_state: &mut ChessGame) -> Result<(), String> {
+    let args = Args::parse(std::env::args().skip(1))?;
+    let player = if args.black { Player::Black } else { Player::White };
+    if let Some(port) = args.host {
+        let address = game_state.host_game(port, player).map_err(|error| format!("couldn't host the game: {error}"))?;
+        println!("waiting for the opponent on port {}", address.port());
+    } else if let Some(address) = args.join {
+        if args.black {
+            println!("the host picks the sides, --black is ignored");
+        }
+        let address = address.parse().map_err(|error| format!("bad address: {error}"))?;
+        game_state.join_game(address);
+    }
+    Ok(())
+}